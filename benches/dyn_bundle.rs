@@ -0,0 +1,155 @@
+use std::hint::black_box;
+
+use bevy_ecs::prelude::*;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use dynamic_bundling::{dynb, DynBundle, DynBundleWorldExt};
+
+#[derive(Component, Clone)]
+struct Counter(u32);
+
+#[derive(Component, Clone)]
+struct Marker;
+
+macro_rules! components {
+    ($( $name:ident ),*) => {
+        $(
+            #[derive(Component, Clone)]
+            struct $name([u32; 4]);
+        )*
+
+        /// A bundle that inserts each component in its own operation.
+        fn wide() -> DynBundle {
+            DynBundle::new() $( .add($name([0; 4])) )*
+        }
+    };
+}
+
+components!(C0, C1, C2, C3, C4, C5, C6, C7);
+
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    for len in [10, 100, 1000] {
+        group.bench_function(format!("add_{len}"), |b| {
+            b.iter(|| (0..len).fold(DynBundle::new(), |acc, i| acc.add(Counter(black_box(i)))))
+        });
+
+        let base = (0..len).fold(DynBundle::new(), |acc, i| acc.add(Counter(i)));
+        group.bench_function(format!("clone_add_{len}"), |b| {
+            b.iter(|| black_box(base.clone()).add(Marker))
+        });
+        group.bench_function(format!("append_{len}"), |b| {
+            b.iter(|| black_box(base.clone()).append(base.clone()))
+        });
+    }
+    group.bench_function("macro_32", |b| {
+        b.iter(|| {
+            dynb!(
+                add(Counter(0)),
+                add(Counter(1)),
+                add(Counter(2)),
+                add(Counter(3)),
+                add(Counter(4)),
+                add(Counter(5)),
+                add(Counter(6)),
+                add(Counter(7)),
+                add(Counter(8)),
+                add(Counter(9)),
+                add(Counter(10)),
+                add(Counter(11)),
+                add(Counter(12)),
+                add(Counter(13)),
+                add(Counter(14)),
+                add(Counter(15)),
+                add(Counter(16)),
+                add(Counter(17)),
+                add(Counter(18)),
+                add(Counter(19)),
+                add(Counter(20)),
+                add(Counter(21)),
+                add(Counter(22)),
+                add(Counter(23)),
+                add(Counter(24)),
+                add(Counter(25)),
+                add(Counter(26)),
+                add(Counter(27)),
+                add(Counter(28)),
+                add(Counter(29)),
+                add(Counter(30)),
+                add(Counter(31)),
+            )
+        })
+    });
+    group.finish();
+}
+
+fn apply(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply");
+    for len in [10, 100, 1000] {
+        let dyn_bundle = (0..len).fold(DynBundle::new(), |acc, i| acc.add(Counter(i)));
+        group.bench_function(format!("apply_to_{len}"), |b| {
+            b.iter_batched_ref(
+                World::new,
+                |world| dyn_bundle.apply_to(&mut world.spawn_empty()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn batched(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batched");
+    let dyn_bundle = wide();
+    // Entities accumulate in one world, so archetypes are only created by the first iteration.
+    group.bench_function("apply_to", |b| {
+        let mut world = World::new();
+        b.iter(|| dyn_bundle.apply_to(&mut world.spawn_empty()))
+    });
+    group.bench_function("apply_batched_to", |b| {
+        let mut world = World::new();
+        b.iter(|| dyn_bundle.apply_batched_to(&mut world.spawn_empty()))
+    });
+    group.finish();
+}
+
+fn spawn_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spawn_batch");
+    let dyn_bundle = wide();
+    for count in [100, 10_000] {
+        group.bench_function(format!("spawn_dyn_batch_{count}"), |b| {
+            b.iter_batched_ref(
+                World::new,
+                |world| world.spawn_dyn_batch(dyn_bundle.clone(), count),
+                BatchSize::SmallInput,
+            )
+        });
+        // Inserting the bundle as a component queues one command per entity.
+        group.bench_function(format!("component_hook_{count}"), |b| {
+            b.iter_batched_ref(
+                World::new,
+                |world| {
+                    world
+                        .spawn_batch(std::iter::repeat_n(dyn_bundle.clone(), count))
+                        .for_each(drop);
+                    world.flush();
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(format!("spawn_dyn_{count}"), |b| {
+            b.iter_batched_ref(
+                World::new,
+                |world| {
+                    for _ in 0..count {
+                        world.spawn_dyn(dyn_bundle.clone());
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, build, apply, batched, spawn_batch);
+criterion_main!(benches);
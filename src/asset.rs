@@ -0,0 +1,140 @@
+use std::fmt;
+
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_asset::{
+    io::Reader, Asset, AssetApp, AssetEvent, AssetId, AssetLoader, Assets, Handle, LoadContext,
+};
+use bevy_ecs::{prelude::*, reflect::AppTypeRegistry};
+use bevy_reflect::{TypePath, TypeRegistryArc};
+use serde::de::DeserializeSeed;
+
+use crate::{serde::DynBundleDeserializer, DynBundle};
+
+/// Loads `.dynb.ron` files into [`DynBundleAsset`]s and applies them to entities holding a
+/// [`DynBundleHandle`], re-applying them whenever the asset is modified.
+pub struct DynBundleAssetPlugin;
+
+impl Plugin for DynBundleAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<DynBundleAsset>()
+            .init_asset_loader::<DynBundleAssetLoader>()
+            .add_systems(PreUpdate, apply_dyn_bundle_assets);
+    }
+}
+
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct DynBundleAsset(pub DynBundle);
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct DynBundleHandle(pub Handle<DynBundleAsset>);
+
+fn apply_dyn_bundle_assets(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<DynBundleAsset>>,
+    assets: Res<Assets<DynBundleAsset>>,
+    added_handles: Query<(Entity, &DynBundleHandle), Changed<DynBundleHandle>>,
+    handles: Query<(Entity, &DynBundleHandle)>,
+) {
+    let mut changed = Vec::<AssetId<DynBundleAsset>>::new();
+    for event in events.read() {
+        if let AssetEvent::Added { id } | AssetEvent::Modified { id } = event {
+            if !changed.contains(id) {
+                changed.push(*id);
+            }
+        }
+    }
+
+    let mut apply = |entity: Entity, handle: &DynBundleHandle| {
+        if let Some(asset) = assets.get(&handle.0) {
+            commands.entity(entity).insert(asset.0.clone());
+        }
+    };
+    if changed.is_empty() {
+        for (entity, handle) in &added_handles {
+            apply(entity, handle);
+        }
+    } else {
+        for (entity, handle) in &handles {
+            if changed.contains(&handle.0.id()) || added_handles.contains(entity) {
+                apply(entity, handle);
+            }
+        }
+    }
+}
+
+pub struct DynBundleAssetLoader {
+    type_registry: TypeRegistryArc,
+}
+
+impl FromWorld for DynBundleAssetLoader {
+    fn from_world(world: &mut World) -> Self {
+        DynBundleAssetLoader {
+            type_registry: world.resource::<AppTypeRegistry>().0.clone(),
+        }
+    }
+}
+
+impl AssetLoader for DynBundleAssetLoader {
+    type Asset = DynBundleAsset;
+    type Settings = ();
+    type Error = DynBundleAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<DynBundleAsset, DynBundleAssetLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut deserializer = ron::de::Deserializer::from_bytes(&bytes)?;
+        let bundle = DynBundleDeserializer::new(&self.type_registry.read())
+            .deserialize(&mut deserializer)
+            .map_err(|e| deserializer.span_error(e))?;
+        Ok(DynBundleAsset(bundle))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dynb.ron"]
+    }
+}
+
+#[derive(Debug)]
+pub enum DynBundleAssetLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for DynBundleAssetLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynBundleAssetLoaderError::Io(error) => {
+                write!(f, "could not read DynBundle asset: {error}")
+            }
+            DynBundleAssetLoaderError::Ron(error) => {
+                write!(f, "could not parse DynBundle asset: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DynBundleAssetLoaderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DynBundleAssetLoaderError::Io(error) => Some(error),
+            DynBundleAssetLoaderError::Ron(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for DynBundleAssetLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        DynBundleAssetLoaderError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for DynBundleAssetLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        DynBundleAssetLoaderError::Ron(error)
+    }
+}
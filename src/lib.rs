@@ -1,20 +1,307 @@
-#![feature(specialization)]
-#![allow(incomplete_features)]
-
-use std::sync::Arc;
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    fmt,
+    sync::{Arc, Mutex},
+};
 
 use bevy_ecs::{
-    component::{ComponentHooks, StorageType},
+    component::{ComponentId, Components},
+    entity::EntityHashSet,
     prelude::*,
-    world::Command,
+    reflect::{AppTypeRegistry, ReflectComponent},
+    storage::Storages,
+    world::{Command, DeferredWorld},
 };
+use bevy_hierarchy::{BuildChildren, Children, DespawnRecursiveExt, Parent};
+use bevy_reflect::PartialReflect;
+use bevy_utils::{tracing::warn, TypeIdMap};
+
+#[cfg(feature = "asset")]
+pub mod asset;
+#[cfg(feature = "serialize")]
+pub mod serde;
+mod staging;
+
+use staging::Staging;
+
+type BundleFn = Arc<dyn Fn(&mut EntityWorldMut, InsertMode) + Send + Sync>;
+type StageFn = Arc<dyn Fn(&mut EntityWorldMut, InsertMode, &mut Staging) + Send + Sync>;
+
+#[derive(Clone)]
+struct BundleOp {
+    kind: OpKind,
+    /// Names of the groups added with [`DynBundle::add_named`] that contain this operation,
+    /// outermost first.
+    names: Vec<Cow<'static, str>>,
+    mode: InsertMode,
+    apply: BundleFn,
+    /// Set for operations that [`DynBundle::apply_batched_to`] can stage instead of applying.
+    stage: Option<StageFn>,
+}
+
+impl BundleOp {
+    fn info(&self) -> OpInfo {
+        match (self.kind.info(), self.mode) {
+            (OpInfo::Insert(components), InsertMode::Keep) => OpInfo::InsertIfNew(components),
+            (info, _) => info,
+        }
+    }
+}
 
-type BundleFn = Arc<dyn Fn(&mut EntityWorldMut) + Send + Sync>;
+/// Whether inserting operations overwrite components that are already on the entity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum InsertMode {
+    #[default]
+    Replace,
+    Keep,
+}
 
 #[derive(Clone)]
+enum OpKind {
+    Insert(fn() -> Vec<ComponentDesc>),
+    /// Inserted by [`DynBundle::add_once`], which can only be applied once.
+    InsertOnce(fn() -> Vec<ComponentDesc>),
+    Remove(fn() -> Vec<ComponentDesc>),
+    InsertReflect(Arc<dyn PartialReflect>),
+    RemoveReflect(Cow<'static, str>),
+    RemoveIds(Arc<[ComponentId]>),
+    RemoveComponents(Arc<[ComponentDesc]>),
+    Retain(fn() -> Vec<ComponentDesc>),
+    Modify(fn() -> Vec<ComponentDesc>),
+    Child(DynBundle),
+    Custom,
+    OnApplied,
+    /// Marks where a named group starts, so the group keeps its position even when it has no
+    /// operations. Applying it does nothing, and it is hidden from [`DynBundle::ops`].
+    GroupStart,
+    /// Despawns children spawned by a bundle applied with [`DynBundle::apply_with_undo`]. Holds
+    /// the `Child` operations that spawned them, so undoing the despawn spawns them again.
+    DespawnChildren(DynBundle),
+}
+
+impl OpKind {
+    fn info(&self) -> OpInfo {
+        match self {
+            OpKind::Insert(components) | OpKind::InsertOnce(components) => {
+                OpInfo::Insert(components())
+            }
+            OpKind::Remove(components) => OpInfo::Remove(components()),
+            OpKind::InsertReflect(component) => {
+                let desc = match component.get_represented_type_info() {
+                    Some(type_info) => ComponentDesc {
+                        type_id: Some(type_info.type_id()),
+                        name: Cow::Borrowed(type_info.type_path()),
+                    },
+                    None => ComponentDesc {
+                        type_id: None,
+                        name: Cow::Owned(component.reflect_type_path().to_owned()),
+                    },
+                };
+                OpInfo::Insert(vec![desc])
+            }
+            OpKind::RemoveReflect(type_path) => OpInfo::Remove(vec![ComponentDesc {
+                type_id: None,
+                name: type_path.clone(),
+            }]),
+            OpKind::RemoveIds(component_ids) => OpInfo::RemoveIds(component_ids.to_vec()),
+            OpKind::RemoveComponents(components) => OpInfo::Remove(components.to_vec()),
+            OpKind::Retain(components) => OpInfo::Retain(components()),
+            OpKind::Modify(components) => OpInfo::Modify(components()),
+            OpKind::Child(child) => OpInfo::Child(child.clone()),
+            OpKind::Custom | OpKind::GroupStart | OpKind::DespawnChildren(_) => OpInfo::Custom,
+            OpKind::OnApplied => OpInfo::OnApplied,
+        }
+    }
+}
+
+/// Describes a single operation of a [`DynBundle`], as returned by [`DynBundle::ops`].
+#[derive(Clone, Debug)]
+pub enum OpInfo {
+    Insert(Vec<ComponentDesc>),
+    InsertIfNew(Vec<ComponentDesc>),
+    Remove(Vec<ComponentDesc>),
+    RemoveIds(Vec<ComponentId>),
+    /// Removes every component except the listed ones.
+    Retain(Vec<ComponentDesc>),
+    Modify(Vec<ComponentDesc>),
+    Child(DynBundle),
+    /// A closure added with [`DynBundle::with`] or [`DynBundle::with_world`].
+    Custom,
+    /// A callback added with [`DynBundle::on_applied`].
+    OnApplied,
+}
+
+impl OpInfo {
+    pub fn components(&self) -> &[ComponentDesc] {
+        match self {
+            OpInfo::Insert(components)
+            | OpInfo::InsertIfNew(components)
+            | OpInfo::Remove(components)
+            | OpInfo::Modify(components) => components,
+            OpInfo::RemoveIds(_)
+            | OpInfo::Retain(_)
+            | OpInfo::Child(_)
+            | OpInfo::Custom
+            | OpInfo::OnApplied => &[],
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ComponentDesc {
+    type_id: Option<TypeId>,
+    name: Cow<'static, str>,
+}
+
+impl ComponentDesc {
+    /// Returns `None` for components that are only known by their type path.
+    pub fn type_id(&self) -> Option<TypeId> {
+        self.type_id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn of_bundle<B: Bundle>() -> Vec<ComponentDesc> {
+        let mut components = Components::default();
+        let mut storages = Storages::default();
+        let mut ids = Vec::new();
+        B::component_ids(&mut components, &mut storages, &mut |id| ids.push(id));
+        ids.into_iter()
+            .filter_map(|id| components.get_info(id))
+            .map(|info| ComponentDesc {
+                type_id: info.type_id(),
+                name: Cow::Owned(info.name().to_owned()),
+            })
+            .collect()
+    }
+}
+
+/// Tracks whether each component ends up present or absent after applying a bundle.
+#[derive(Default)]
+struct Presence {
+    components: Vec<(ComponentDesc, bool)>,
+    indices: TypeIdMap<usize>,
+}
+
+impl Presence {
+    fn of(dyn_bundle: &DynBundle) -> Self {
+        let mut presence = Presence::default();
+        for info in dyn_bundle.ops() {
+            match &info {
+                OpInfo::Insert(components) | OpInfo::InsertIfNew(components) => {
+                    presence.set_all(components, true);
+                }
+                OpInfo::Remove(components) => presence.set_all(components, false),
+                OpInfo::Retain(kept) => {
+                    for (component, present) in &mut presence.components {
+                        if !kept.iter().any(|kept| kept.type_id == component.type_id) {
+                            *present = false;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        presence
+    }
+
+    fn set_all(&mut self, components: &[ComponentDesc], present: bool) {
+        for component in components {
+            let Some(type_id) = component.type_id else {
+                continue;
+            };
+            match self.indices.get(&type_id) {
+                Some(&index) => self.components[index].1 = present,
+                None => {
+                    self.indices.insert(type_id, self.components.len());
+                    self.components.push((component.clone(), present));
+                }
+            }
+        }
+    }
+
+    fn get(&self, type_id: TypeId) -> Option<bool> {
+        self.indices
+            .get(&type_id)
+            .map(|&index| self.components[index].1)
+    }
+}
+
+fn insert_reflect(
+    entity: &mut EntityWorldMut,
+    mode: InsertMode,
+    component: &dyn PartialReflect,
+) -> Result<(), DynBundleError> {
+    let Some(type_info) = component.get_represented_type_info() else {
+        return Err(DynBundleError::MissingTypeInfo(Cow::Owned(
+            component.reflect_type_path().to_owned(),
+        )));
+    };
+    if mode == InsertMode::Keep && entity.contains_type_id(type_info.type_id()) {
+        return Ok(());
+    }
+    let Some(registry) = entity.world().get_resource::<AppTypeRegistry>().cloned() else {
+        return Err(DynBundleError::MissingTypeRegistry);
+    };
+    let registry = registry.read();
+    let Some(reflect_component) = registry.get_type_data::<ReflectComponent>(type_info.type_id())
+    else {
+        return Err(DynBundleError::UnregisteredComponent(Cow::Borrowed(
+            type_info.type_path(),
+        )));
+    };
+    reflect_component.insert(entity, component, &registry);
+    Ok(())
+}
+
+fn remove_reflect(entity: &mut EntityWorldMut, type_path: &str) -> Result<(), DynBundleError> {
+    let Some(registry) = entity.world().get_resource::<AppTypeRegistry>().cloned() else {
+        return Err(DynBundleError::MissingTypeRegistry);
+    };
+    let registry = registry.read();
+    let Some(reflect_component) = registry
+        .get_with_type_path(type_path)
+        .and_then(|registration| registration.data::<ReflectComponent>())
+    else {
+        return Err(DynBundleError::UnregisteredComponent(Cow::Owned(
+            type_path.to_owned(),
+        )));
+    };
+    reflect_component.remove(entity);
+    Ok(())
+}
+
+impl fmt::Debug for ComponentDesc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// An ordered list of entity operations that is applied when inserted as a component.
+///
+/// Operations are applied left to right in the order they were added, so when two
+/// operations touch the same component the last one wins. [`DynBundle::append`] keeps
+/// this order by running the appended operations after the existing ones, while
+/// [`DynBundle::prepend`] runs them before, letting the existing operations win.
+///
+/// Builder methods take the bundle by value and return it, so chains like
+/// `DynBundle::new().add(a).del::<B>()` move a single list along instead of copying it.
+/// Cloning a `DynBundle` is cheap, because clones share the list, but it is copy-on-write
+/// rather than structurally shared: the first builder call on a clone whose list is still
+/// shared copies it, which costs one reference count increment per operation.
+#[derive(Component, Clone, Default)]
+#[component(
+    storage = "SparseSet",
+    on_insert = queue_dyn_bundle,
+    on_replace = keep_replaced_dyn_bundle,
+    on_remove = drop_replaced_dyn_bundles
+)]
+#[require(ReplacedDynBundles)]
 pub struct DynBundle {
-    bundle: BundleFn,
-    parent: Option<Arc<DynBundle>>,
+    ops: Arc<Vec<BundleOp>>,
 }
 
 impl DynBundle {
@@ -26,7 +313,7 @@ impl DynBundle {
         DynBundle::default().add(bundle)
     }
 
-    pub fn new_del<B: Bundle + Clone>(&self) -> Self {
+    pub fn new_del<B: Bundle + Clone>() -> Self {
         DynBundle::default().del::<B>()
     }
 
@@ -34,62 +321,749 @@ impl DynBundle {
         DynBundle::default().append_many(iter)
     }
 
-    pub fn add<B: Bundle + Clone>(&self, bundle: B) -> Self {
-        DynBundle {
-            bundle: Arc::new(move |entity: &mut EntityWorldMut| {
-                entity.insert(bundle.clone());
+    pub fn add<B: Bundle + Clone>(self, bundle: B) -> Self {
+        self.insert_value(move || bundle.clone())
+    }
+
+    /// Inserts a bundle that doesn't implement `Clone`.
+    ///
+    /// The bundle is moved into the entity the first time the operation is applied. Clones
+    /// of this `DynBundle` share it, so applying any of them again, for example through
+    /// [`DynBundleWorldExt::spawn_dyn_batch`] or [`UndoStack::redo`], inserts nothing and
+    /// reports [`DynBundleError::AlreadyApplied`] instead.
+    pub fn add_once<B: Bundle>(self, bundle: B) -> Self {
+        let bundle = Mutex::new(Some(bundle));
+        self.insert_value_or_report(
+            OpKind::InsertOnce(ComponentDesc::of_bundle::<B>),
+            move || {
+                bundle
+                    .lock()
+                    .unwrap()
+                    .take()
+                    .ok_or(DynBundleError::AlreadyApplied)
+            },
+        )
+    }
+
+    pub fn add_from_world<B: Bundle>(
+        self,
+        f: impl Fn(&World, Entity) -> B + Send + Sync + 'static,
+    ) -> Self {
+        self.insert_with(move |entity| f(entity.world(), entity.id()))
+    }
+
+    pub fn add_default<B: Bundle + Default>(self) -> Self {
+        self.insert_value(B::default)
+    }
+
+    pub fn add_from_world_default<B: Bundle + FromWorld>(self) -> Self {
+        self.insert_with(|entity| entity.world_scope(B::from_world))
+    }
+
+    pub fn add_if_new<B: Bundle + Clone>(self, bundle: B) -> Self {
+        self.append(DynBundle::new_add(bundle).keep_existing())
+    }
+
+    /// Inserts `default` if the entity doesn't have `C` yet, or runs `modify` on the existing
+    /// component otherwise. After [`DynBundle::keep_existing`], an existing component is left
+    /// untouched.
+    pub fn add_or_modify<C: Component + Clone>(
+        self,
+        default: C,
+        modify: impl Fn(&mut C) + Send + Sync + 'static,
+    ) -> Self {
+        self.push(
+            OpKind::Insert(ComponentDesc::of_bundle::<C>),
+            Arc::new(move |entity: &mut EntityWorldMut, mode: InsertMode| {
+                match entity.get_mut::<C>() {
+                    Some(mut component) => {
+                        if mode == InsertMode::Replace {
+                            modify(&mut component);
+                        }
+                    }
+                    None => {
+                        entity.insert(default.clone());
+                    }
+                }
             }),
-            parent: Some(Arc::new(self.clone())),
-        }
+        )
     }
 
-    pub fn del<B: Bundle + Clone>(&self) -> Self {
-        DynBundle {
-            bundle: Arc::new(move |entity: &mut EntityWorldMut| {
+    pub fn del<B: Bundle + Clone>(self) -> Self {
+        self.push_staged(
+            OpKind::Remove(ComponentDesc::of_bundle::<B>),
+            Arc::new(|entity: &mut EntityWorldMut, _: InsertMode| {
                 entity.remove::<B>();
             }),
-            parent: Some(Arc::new(self.clone())),
-        }
+            Arc::new(
+                |entity: &mut EntityWorldMut, _: InsertMode, staging: &mut Staging| {
+                    staging.remove::<B>(entity);
+                },
+            ),
+        )
+    }
+
+    pub fn modify<C: Component>(self, modify: impl Fn(&mut C) + Send + Sync + 'static) -> Self {
+        self.push(
+            OpKind::Modify(ComponentDesc::of_bundle::<C>),
+            Arc::new(move |entity: &mut EntityWorldMut, _: InsertMode| {
+                if let Some(mut component) = entity.get_mut::<C>() {
+                    modify(&mut component);
+                }
+            }),
+        )
+    }
+
+    pub fn with(self, f: impl Fn(&mut EntityWorldMut) + Send + Sync + 'static) -> Self {
+        self.push(
+            OpKind::Custom,
+            Arc::new(move |entity: &mut EntityWorldMut, _: InsertMode| f(entity)),
+        )
+    }
+
+    pub fn with_world(self, f: impl Fn(&mut World, Entity) + Send + Sync + 'static) -> Self {
+        self.push(
+            OpKind::Custom,
+            Arc::new(move |entity: &mut EntityWorldMut, _: InsertMode| {
+                let id = entity.id();
+                entity.world_scope(|world| f(world, id));
+            }),
+        )
+    }
+
+    /// Runs `f` once after every other operation of the bundle has been applied, regardless of
+    /// where it was added.
+    pub fn on_applied(self, f: impl Fn(&mut EntityWorldMut) + Send + Sync + 'static) -> Self {
+        self.push(
+            OpKind::OnApplied,
+            Arc::new(move |entity: &mut EntityWorldMut, _: InsertMode| f(entity)),
+        )
+    }
+
+    /// Inserts the [`DynBundleApplied`] marker once the bundle has been applied.
+    pub fn mark_applied(self) -> Self {
+        self.on_applied(|entity| {
+            entity.insert(DynBundleApplied);
+        })
+    }
+
+    pub fn del_id(self, component_id: ComponentId) -> Self {
+        self.del_ids(&[component_id])
+    }
+
+    /// Removes components by id. Ids of components the entity doesn't have, including ids
+    /// that aren't registered in its world, are skipped.
+    pub fn del_ids(self, component_ids: &[ComponentId]) -> Self {
+        let component_ids: Arc<[ComponentId]> = component_ids.into();
+        self.push(
+            OpKind::RemoveIds(component_ids.clone()),
+            Arc::new(move |entity: &mut EntityWorldMut, _: InsertMode| {
+                for &component_id in component_ids.iter() {
+                    if entity.contains_id(component_id) {
+                        entity.remove_by_id(component_id);
+                    }
+                }
+            }),
+        )
+    }
+
+    pub fn del_all_except<B: Bundle>(self) -> Self {
+        self.push(
+            OpKind::Retain(ComponentDesc::of_bundle::<B>),
+            Arc::new(|entity: &mut EntityWorldMut, _: InsertMode| {
+                entity.retain::<B>();
+            }),
+        )
+    }
+
+    /// Inserts a reflected component through its `ReflectComponent` registration.
+    ///
+    /// A value that doesn't represent a type, or whose type isn't registered, is reported
+    /// through the [`DynBundleErrorPolicy`] when applied, and the remaining operations
+    /// still run.
+    pub fn add_reflect(self, component: Box<dyn PartialReflect>) -> Self {
+        let component: Arc<dyn PartialReflect> = Arc::from(component);
+        self.push(
+            OpKind::InsertReflect(component.clone()),
+            Arc::new(move |entity: &mut EntityWorldMut, mode: InsertMode| {
+                if let Err(reason) = insert_reflect(entity, mode, &*component) {
+                    report_op_error(entity, reason);
+                }
+            }),
+        )
     }
 
-    pub fn append(&self, dyn_bundle: impl IntoDynBundle) -> Self {
+    /// Removes a component by its type path. Unknown type paths are reported like in
+    /// [`DynBundle::add_reflect`].
+    pub fn del_by_type_path(self, type_path: &str) -> Self {
+        let type_path = type_path.to_owned();
+        self.push(
+            OpKind::RemoveReflect(Cow::Owned(type_path.clone())),
+            Arc::new(move |entity: &mut EntityWorldMut, _: InsertMode| {
+                if let Err(reason) = remove_reflect(entity, &type_path) {
+                    report_op_error(entity, reason);
+                }
+            }),
+        )
+    }
+
+    pub fn append(mut self, dyn_bundle: impl IntoDynBundle) -> Self {
         let dyn_bundle = dyn_bundle.into_dynb();
-        DynBundle {
-            bundle: dyn_bundle.bundle.clone(),
-            parent: match dyn_bundle.parent {
-                Some(parent) => Some(Arc::new((*parent).append(self.clone()))),
-                None => Some(Arc::new(self.clone())),
-            },
+        if self.ops.is_empty() {
+            return dyn_bundle;
         }
+        Arc::make_mut(&mut self.ops).extend(dyn_bundle.ops.iter().cloned());
+        self
+    }
+
+    pub fn prepend(self, dyn_bundle: impl IntoDynBundle) -> Self {
+        dyn_bundle.into_dynb().append(self)
+    }
+
+    /// Appends `dyn_bundle` under `name`, so it can later be swapped out with
+    /// [`DynBundle::replace_named`] or dropped with [`DynBundle::remove_named`].
+    pub fn add_named(
+        self,
+        name: impl Into<Cow<'static, str>>,
+        dyn_bundle: impl IntoDynBundle,
+    ) -> Self {
+        self.append(dyn_bundle.into_dynb().with_name(name.into()))
+    }
+
+    /// Replaces the operations added under `name` with `dyn_bundle`, keeping their position.
+    ///
+    /// Does nothing if no operation was added under `name`.
+    pub fn replace_named(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        dyn_bundle: impl IntoDynBundle,
+    ) -> Self {
+        let name = name.into();
+        let Some(index) = self.ops.iter().position(|op| op.names.contains(&name)) else {
+            return self;
+        };
+        // The replacement stays inside the groups that enclose the replaced one.
+        let names = &self.ops[index].names;
+        let outer = names[..names.iter().position(|n| *n == name).unwrap_or(0)].to_vec();
+        let mut replacement = dyn_bundle.into_dynb().with_name(name.clone());
+        for op in Arc::make_mut(&mut replacement.ops) {
+            op.names.splice(0..0, outer.iter().cloned());
+        }
+
+        let ops = Arc::make_mut(&mut self.ops);
+        ops.retain(|op| !op.names.contains(&name));
+        let tail = ops.split_off(index);
+        ops.extend(replacement.ops.iter().cloned());
+        ops.extend(tail);
+        self
+    }
+
+    /// Removes the operations added under `name`, including nested groups.
+    pub fn remove_named(mut self, name: &str) -> Self {
+        let named = |op: &BundleOp| op.names.iter().any(|n| n == name);
+        if self.ops.iter().any(named) {
+            Arc::make_mut(&mut self.ops).retain(|op| !named(op));
+        }
+        self
     }
 
-    pub fn append_some(&self, opt_bundle: Option<impl IntoDynBundle>) -> Self {
-        if let Some(bundle) = opt_bundle {
-            self.append(bundle.into_dynb());
+    pub fn append_some(self, opt_bundle: Option<impl IntoDynBundle>) -> Self {
+        match opt_bundle {
+            Some(bundle) => self.append(bundle),
+            None => self,
         }
-        self.clone()
     }
 
-    pub fn append_many(&self, iter: impl IntoIterator<Item = impl IntoDynBundle>) -> Self {
-        iter.into_iter().fold(self.clone(), |parent, child| {
-            parent.append(child.into_dynb())
+    pub fn append_if(self, condition: bool, dyn_bundle: impl IntoDynBundle) -> Self {
+        if condition {
+            self.append(dyn_bundle)
+        } else {
+            self
+        }
+    }
+
+    pub fn append_with<B: IntoDynBundle>(self, f: impl FnOnce() -> Option<B>) -> Self {
+        self.append_some(f())
+    }
+
+    pub fn append_else(
+        self,
+        condition: bool,
+        then_bundle: impl IntoDynBundle,
+        else_bundle: impl IntoDynBundle,
+    ) -> Self {
+        if condition {
+            self.append(then_bundle)
+        } else {
+            self.append(else_bundle)
+        }
+    }
+
+    pub fn append_many(self, iter: impl IntoIterator<Item = impl IntoDynBundle>) -> Self {
+        iter.into_iter()
+            .fold(self, |parent, child| parent.append(child))
+    }
+
+    pub fn with_child(self, child: impl IntoDynBundle) -> Self {
+        let child = child.into_dynb();
+        self.push(
+            OpKind::Child(child.clone()),
+            Arc::new(move |entity: &mut EntityWorldMut, _: InsertMode| {
+                let child = entity.world_scope(|world| {
+                    let mut child_mut = world.spawn_empty();
+                    child.apply_to(&mut child_mut);
+                    child_mut.id()
+                });
+                entity.add_child(child);
+            }),
+        )
+    }
+
+    pub fn with_children(self, iter: impl IntoIterator<Item = impl IntoDynBundle>) -> Self {
+        iter.into_iter()
+            .fold(self, |parent, child| parent.with_child(child))
+    }
+
+    /// Returns the number of operations, like the length of [`DynBundle::ops`].
+    pub fn len(&self) -> usize {
+        self.visible_ops().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.visible_ops().next().is_none()
+    }
+
+    /// Makes every operation currently in this bundle keep components the entity already
+    /// has instead of overwriting them.
+    pub fn keep_existing(mut self) -> Self {
+        for op in Arc::make_mut(&mut self.ops) {
+            op.mode = InsertMode::Keep;
+        }
+        self
+    }
+
+    pub fn ops(&self) -> Vec<OpInfo> {
+        self.visible_ops().map(BundleOp::info).collect()
+    }
+
+    fn visible_ops(&self) -> impl Iterator<Item = &BundleOp> {
+        self.ops
+            .iter()
+            .filter(|op| !matches!(op.kind, OpKind::GroupStart))
+    }
+
+    pub fn component_type_ids(&self) -> Vec<TypeId> {
+        let mut type_ids = Vec::new();
+        for info in self.ops() {
+            for type_id in info.components().iter().filter_map(ComponentDesc::type_id) {
+                if !type_ids.contains(&type_id) {
+                    type_ids.push(type_id);
+                }
+            }
+        }
+        type_ids
+    }
+
+    /// Returns a bundle that moves an entity configured by `old` to the configuration of `new`.
+    ///
+    /// Components that `old` leaves on the entity but `new` doesn't are removed first, followed
+    /// by the operations of `new` that contribute to its final set of components, in their
+    /// original order. Removals are kept when an earlier kept insertion added the component
+    /// back, or when an operation that can't be introspected was kept before them. Those
+    /// operations, like closures and children, are always kept, so they still see the entity
+    /// as if `new` had been applied from the start, and so are modifications, which may target
+    /// components that `new` doesn't insert itself.
+    pub fn diff(old: &DynBundle, new: &DynBundle) -> DynBundle {
+        let old_presence = Presence::of(old);
+        let new_presence = Presence::of(new);
+
+        let mut removed: Vec<ComponentDesc> = Vec::new();
+        for (component, _) in old_presence
+            .components
+            .iter()
+            .chain(&new_presence.components)
+        {
+            let Some(type_id) = component.type_id else {
+                continue;
+            };
+            let old_state = old_presence.get(type_id);
+            let new_state = new_presence.get(type_id);
+            let remove = match new_state {
+                Some(true) => false,
+                Some(false) => old_state != Some(false),
+                None => old_state == Some(true),
+            };
+            if remove
+                && !removed
+                    .iter()
+                    .any(|removed| removed.type_id == Some(type_id))
+            {
+                removed.push(component.clone());
+            }
+        }
+
+        let mut ops: Vec<BundleOp> = Vec::new();
+        if !removed.is_empty() {
+            ops.push(BundleOp {
+                kind: OpKind::RemoveComponents(removed.clone().into()),
+                names: Vec::new(),
+                mode: InsertMode::Replace,
+                stage: None,
+                apply: Arc::new(move |entity: &mut EntityWorldMut, _: InsertMode| {
+                    for type_id in removed.iter().filter_map(ComponentDesc::type_id) {
+                        if let Some(component_id) = entity.world().components().get_id(type_id) {
+                            entity.remove_by_id(component_id);
+                        }
+                    }
+                }),
+            });
+        }
+
+        // Components inserted by the operations kept so far.
+        let mut inserted: Vec<TypeId> = Vec::new();
+        // Whether an operation kept so far may have inserted components that aren't listed.
+        let mut opaque = false;
+        for op in new.ops.iter() {
+            let info = op.info();
+            let keep = match &info {
+                OpInfo::Insert(components) | OpInfo::InsertIfNew(components) => {
+                    components.iter().any(|component| {
+                        component
+                            .type_id
+                            .is_none_or(|type_id| new_presence.get(type_id) == Some(true))
+                    })
+                }
+                OpInfo::Remove(components) => {
+                    opaque
+                        || components.iter().any(|component| {
+                            component
+                                .type_id
+                                .is_none_or(|type_id| inserted.contains(&type_id))
+                        })
+                }
+                _ => true,
+            };
+            if !keep {
+                continue;
+            }
+            opaque |= match &info {
+                OpInfo::Insert(components)
+                | OpInfo::InsertIfNew(components)
+                | OpInfo::Modify(components) => components
+                    .iter()
+                    .any(|component| component.type_id.is_none()),
+                OpInfo::RemoveIds(_) | OpInfo::Retain(_) | OpInfo::Child(_) | OpInfo::Custom => {
+                    true
+                }
+                OpInfo::Remove(_) | OpInfo::OnApplied => false,
+            };
+            if let OpInfo::Insert(components) | OpInfo::InsertIfNew(components) = &info {
+                inserted.extend(components.iter().filter_map(ComponentDesc::type_id));
+            }
+            ops.push(op.clone());
+        }
+
+        DynBundle { ops: Arc::new(ops) }
+    }
+
+    fn with_name(self, name: Cow<'static, str>) -> Self {
+        let mut group = DynBundle::new().push_staged(
+            OpKind::GroupStart,
+            Arc::new(|_: &mut EntityWorldMut, _: InsertMode| {}),
+            Arc::new(|_: &mut EntityWorldMut, _: InsertMode, _: &mut Staging| {}),
+        );
+        let ops = Arc::make_mut(&mut group.ops);
+        ops.extend(self.ops.iter().cloned());
+        for op in ops {
+            op.names.insert(0, name.clone());
+        }
+        group
+    }
+
+    fn insert_with<B: Bundle>(
+        self,
+        make: impl Fn(&mut EntityWorldMut) -> B + Send + Sync + 'static,
+    ) -> Self {
+        self.push(
+            OpKind::Insert(ComponentDesc::of_bundle::<B>),
+            Arc::new(move |entity: &mut EntityWorldMut, mode: InsertMode| {
+                let bundle = make(entity);
+                insert_bundle(entity, mode, bundle);
+            }),
+        )
+    }
+
+    /// Like [`DynBundle::insert_with`], but for bundles built without access to the entity,
+    /// which allows [`DynBundle::apply_batched_to`] to stage them.
+    fn insert_value<B: Bundle>(self, make: impl Fn() -> B + Send + Sync + 'static) -> Self {
+        self.insert_value_or_report(OpKind::Insert(ComponentDesc::of_bundle::<B>), move || {
+            Ok(make())
         })
     }
 
-    fn apply(&self, entity_mut: &mut EntityWorldMut) {
-        if let Some(ref parent) = self.parent {
-            parent.apply(entity_mut);
+    /// Like [`DynBundle::insert_value`], but `make` can fail, which is reported through the
+    /// [`DynBundleErrorPolicy`].
+    fn insert_value_or_report<B: Bundle>(
+        self,
+        kind: OpKind,
+        make: impl Fn() -> Result<B, DynBundleError> + Send + Sync + 'static,
+    ) -> Self {
+        let make = Arc::new(make);
+        let stage_make = make.clone();
+        self.push_staged(
+            kind,
+            Arc::new(
+                move |entity: &mut EntityWorldMut, mode: InsertMode| match make() {
+                    Ok(bundle) => insert_bundle(entity, mode, bundle),
+                    Err(reason) => report_op_error(entity, reason),
+                },
+            ),
+            Arc::new(
+                move |entity: &mut EntityWorldMut, mode: InsertMode, staging: &mut Staging| {
+                    match stage_make() {
+                        Ok(bundle) => staging.insert(entity, mode, bundle),
+                        Err(reason) => report_op_error(entity, reason),
+                    }
+                },
+            ),
+        )
+    }
+
+    fn push(mut self, kind: OpKind, apply: BundleFn) -> Self {
+        Arc::make_mut(&mut self.ops).push(BundleOp {
+            kind,
+            names: Vec::new(),
+            mode: InsertMode::Replace,
+            apply,
+            stage: None,
+        });
+        self
+    }
+
+    fn retain_ops(mut self, keep: impl Fn(&BundleOp) -> bool) -> Self {
+        Arc::make_mut(&mut self.ops).retain(keep);
+        self
+    }
+
+    fn push_staged(mut self, kind: OpKind, apply: BundleFn, stage: StageFn) -> Self {
+        Arc::make_mut(&mut self.ops).push(BundleOp {
+            kind,
+            names: Vec::new(),
+            mode: InsertMode::Replace,
+            apply,
+            stage: Some(stage),
+        });
+        self
+    }
+
+    pub fn apply_to(&self, entity_mut: &mut EntityWorldMut) {
+        for op in self.ops.iter() {
+            if !matches!(op.kind, OpKind::OnApplied) {
+                (op.apply)(entity_mut, op.mode);
+            }
         }
-        (self.bundle)(entity_mut);
+        self.run_on_applied(entity_mut);
     }
-}
 
-impl Default for DynBundle {
-    fn default() -> Self {
-        DynBundle {
-            bundle: Arc::new(|_| ()),
-            parent: None,
+    /// Applies this bundle like [`DynBundle::apply_to`], but merges consecutive `add` and `del`
+    /// operations into their net effect first, so each run of them moves the entity to a new
+    /// archetype at most once for all insertions, plus once per removed component.
+    ///
+    /// Other operations, like closures or children, still see the entity as if every
+    /// operation before them had been applied.
+    pub fn apply_batched_to(&self, entity_mut: &mut EntityWorldMut) {
+        self.apply_staged(entity_mut, &mut Staging::default());
+    }
+
+    /// Applies this bundle like [`DynBundle::apply_batched_to`] with a `staging` that may be
+    /// reused across entities of the same world.
+    fn apply_staged(&self, entity_mut: &mut EntityWorldMut, staging: &mut Staging) {
+        for op in self.ops.iter() {
+            if matches!(op.kind, OpKind::OnApplied) {
+                continue;
+            }
+            match &op.stage {
+                Some(stage) => stage(entity_mut, op.mode, staging),
+                None => {
+                    staging.flush(entity_mut);
+                    (op.apply)(entity_mut, op.mode);
+                }
+            }
+        }
+        staging.flush(entity_mut);
+        self.run_on_applied(entity_mut);
+    }
+
+    /// Applies this bundle like [`DynBundle::apply_to`] and returns a bundle that undoes it.
+    ///
+    /// The returned bundle removes the components this one added and restores the previous
+    /// values of the ones it changed or removed, which are captured through `ReflectComponent`
+    /// before applying. Components that aren't registered with it can't be restored and are
+    /// skipped with a warning. When every operation lists its components, only those are
+    /// captured, so changes that hooks or observers make to other components are skipped with
+    /// a warning too.
+    ///
+    /// `Parent` and `Children` are left to the hierarchy instead: the returned bundle despawns
+    /// the children this one spawned, recursively, and undoing that spawns them again from
+    /// the [`DynBundle::with_child`] operations. Children spawned by closures are despawned
+    /// too, but not spawned again.
+    pub fn apply_with_undo(&self, entity_mut: &mut EntityWorldMut) -> DynBundle {
+        // Without closures or reflected removals, only the listed components can change, so
+        // the others don't need a snapshot.
+        let touched = self
+            .ops()
+            .iter()
+            .all(|info| {
+                matches!(
+                    info,
+                    OpInfo::Insert(_)
+                        | OpInfo::InsertIfNew(_)
+                        | OpInfo::Remove(_)
+                        | OpInfo::Modify(_)
+                ) && info
+                    .components()
+                    .iter()
+                    .all(|component| component.type_id.is_some())
+            })
+            .then(|| self.component_type_ids());
+
+        let registry = entity_mut
+            .world()
+            .get_resource::<AppTypeRegistry>()
+            .cloned();
+        let hierarchy = [
+            entity_mut.world().components().component_id::<Parent>(),
+            entity_mut.world().components().component_id::<Children>(),
+        ];
+        let children_before = children_of(entity_mut);
+        let mut before: Vec<(ComponentId, Snapshot)> = Vec::new();
+        {
+            let registry = registry.as_ref().map(|registry| registry.read());
+            let world = entity_mut.world();
+            for component_id in entity_mut.archetype().components() {
+                if hierarchy.contains(&Some(component_id)) {
+                    continue;
+                }
+                let type_id = world
+                    .components()
+                    .get_info(component_id)
+                    .and_then(|info| info.type_id());
+                let skipped = type_id.is_some_and(|type_id| {
+                    touched
+                        .as_ref()
+                        .is_some_and(|touched| !touched.contains(&type_id))
+                });
+                let snapshot = if skipped {
+                    Snapshot::Skipped
+                } else {
+                    type_id
+                        .and_then(|type_id| {
+                            registry
+                                .as_ref()?
+                                .get_type_data::<ReflectComponent>(type_id)
+                        })
+                        .and_then(|reflect_component| reflect_component.reflect(&*entity_mut))
+                        .map_or(Snapshot::Unregistered, |component| {
+                            Snapshot::Value(component.clone_value())
+                        })
+                };
+                before.push((component_id, snapshot));
+            }
+        }
+
+        let last_run = entity_mut.world_scope(|world| world.increment_change_tick());
+        self.apply_to(entity_mut);
+        let this_run = entity_mut.world().change_tick();
+
+        let spawned: Vec<Entity> = children_of(entity_mut)
+            .into_iter()
+            .filter(|child| !children_before.contains(child))
+            .collect();
+        let mut undo = DynBundle::new();
+        if !spawned.is_empty() {
+            let respawn = self
+                .clone()
+                .retain_ops(|op| matches!(op.kind, OpKind::Child(_)));
+            undo = undo.push(
+                OpKind::DespawnChildren(respawn),
+                Arc::new(move |entity: &mut EntityWorldMut, _: InsertMode| {
+                    entity.world_scope(|world| {
+                        for &child in &spawned {
+                            if let Ok(child) = world.get_entity_mut(child) {
+                                child.despawn_recursive();
+                            }
+                        }
+                    });
+                }),
+            );
         }
+        // Applying may have registered the hierarchy components, so look them up again.
+        let hierarchy = [
+            entity_mut.world().components().component_id::<Parent>(),
+            entity_mut.world().components().component_id::<Children>(),
+        ];
+        for component_id in entity_mut.archetype().components() {
+            if !hierarchy.contains(&Some(component_id))
+                && !before.iter().any(|&(id, _)| id == component_id)
+            {
+                undo = undo.del_id(component_id);
+            }
+        }
+        for (component_id, snapshot) in before {
+            let changed = entity_mut
+                .get_change_ticks_by_id(component_id)
+                .is_none_or(|ticks| ticks.is_changed(last_run, this_run));
+            if !changed {
+                continue;
+            }
+            let name = || {
+                entity_mut
+                    .world()
+                    .components()
+                    .get_info(component_id)
+                    .map_or("<unknown>", |info| info.name())
+                    .to_owned()
+            };
+            match snapshot {
+                Snapshot::Value(component) => undo = undo.add_reflect(component),
+                Snapshot::Skipped => warn!(
+                    "can't undo changes to `{}`, it was changed outside of the bundle's operations",
+                    name()
+                ),
+                Snapshot::Unregistered => warn!(
+                    "can't undo changes to `{}`, it isn't registered with `ReflectComponent`",
+                    name()
+                ),
+            }
+        }
+        for op in self.ops.iter() {
+            if let OpKind::DespawnChildren(respawn) = &op.kind {
+                undo = undo.append(respawn.clone());
+            }
+        }
+        undo
+    }
+
+    fn run_on_applied(&self, entity_mut: &mut EntityWorldMut) {
+        for op in self.ops.iter() {
+            if matches!(op.kind, OpKind::OnApplied) {
+                (op.apply)(entity_mut, op.mode);
+            }
+        }
+    }
+}
+
+fn insert_bundle<B: Bundle>(entity: &mut EntityWorldMut, mode: InsertMode, bundle: B) {
+    if mode == InsertMode::Keep {
+        entity.insert_if_new(bundle);
+    } else {
+        entity.insert(bundle);
+    }
+}
+
+impl fmt::Debug for DynBundle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.ops()).finish()
     }
 }
 
@@ -98,68 +1072,499 @@ pub trait IntoDynBundle {
 }
 
 impl<B: Bundle + Clone> IntoDynBundle for B {
-    default fn into_dynb(self) -> DynBundle {
-        DynBundle::new_add(self.clone())
+    fn into_dynb(self) -> DynBundle {
+        // `DynBundle` is a `Bundle` itself, so it has to be passed through here instead of
+        // being inserted as a component. Checking the type at runtime keeps this a single
+        // blanket impl, which works on stable.
+        let mut bundle = Some(self);
+        match (&mut bundle as &mut dyn Any).downcast_mut::<Option<DynBundle>>() {
+            Some(dyn_bundle) => dyn_bundle.take().unwrap_or_default(),
+            None => bundle.map(DynBundle::new_add).unwrap_or_default(),
+        }
     }
 }
 
-impl IntoDynBundle for DynBundle {
-    fn into_dynb(self) -> DynBundle {
-        self
+/// Bundles that were replaced on an entity by inserting another [`DynBundle`] before the
+/// command queued for the first one ran.
+#[derive(Component, Default)]
+#[component(storage = "SparseSet")]
+struct ReplacedDynBundles {
+    bundles: Vec<DynBundle>,
+    /// Whether a [`DynBundleCommand`] for the entity is still queued.
+    queued: bool,
+}
+
+fn queue_dyn_bundle(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let Some(mut replaced) = world.get_mut::<ReplacedDynBundles>(entity) else {
+        return;
+    };
+    if replaced.queued {
+        return;
     }
+    replaced.queued = true;
+    world.commands().queue(DynBundleCommand { entity });
 }
 
-impl Component for DynBundle {
-    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+fn keep_replaced_dyn_bundle(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let policy = world
+        .get_resource::<DynBundleReinsertPolicy>()
+        .copied()
+        .unwrap_or_default();
+    if policy == DynBundleReinsertPolicy::Replace {
+        return;
+    }
+    let Some(dyn_bundle) = world.get::<DynBundle>(entity).cloned() else {
+        return;
+    };
+    // The command takes the list before the bundle, so removing the bundle there doesn't
+    // keep it.
+    if let Some(mut replaced) = world.get_mut::<ReplacedDynBundles>(entity) {
+        replaced.bundles.push(dyn_bundle);
+    }
+}
 
-    fn register_component_hooks(hooks: &mut ComponentHooks) {
-        hooks.on_add(|mut world, entity, _| {
-            world.commands().queue(DynBundleCommand(entity));
-        });
+fn drop_replaced_dyn_bundles(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    // Removing the bundle before it was applied cancels the replaced ones too. The command
+    // stays queued and applies a bundle inserted again in the meantime.
+    if let Some(mut replaced) = world.get_mut::<ReplacedDynBundles>(entity) {
+        replaced.bundles.clear();
+    }
+}
+
+/// Applies the bundles replaced on an entity in insertion order, followed by the one still on
+/// it, and removes all of them.
+struct DynBundleCommand {
+    entity: Entity,
+}
+
+impl DynBundleCommand {
+    fn try_apply(&self, world: &mut World) -> Result<(), DynBundleError> {
+        let Ok(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            return Err(DynBundleError::EntityNotFound);
+        };
+        let replaced = entity_mut
+            .take::<ReplacedDynBundles>()
+            .map(|replaced| replaced.bundles)
+            .unwrap_or_default();
+        let Some(dyn_bundle) = entity_mut.take::<DynBundle>() else {
+            return Err(DynBundleError::ComponentNotFound);
+        };
+        // Merged first, so callbacks added with `on_applied` run after all of them.
+        replaced
+            .into_iter()
+            .fold(DynBundle::new(), DynBundle::append)
+            .append(dyn_bundle)
+            .apply_to(&mut entity_mut);
+        world.trigger_targets(OnDynBundleApplied, self.entity);
+        Ok(())
     }
 }
 
-struct DynBundleCommand(Entity);
+/// Controls what happens when a [`DynBundle`] component is inserted again before the
+/// bundle already on the entity has been applied.
+///
+/// Without this resource the [`DynBundleReinsertPolicy::Merge`] policy is used.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DynBundleReinsertPolicy {
+    /// Applies every inserted bundle, in insertion order.
+    #[default]
+    Merge,
+    /// Applies only the last inserted bundle.
+    Replace,
+}
+
+/// Triggered on an entity once a [`DynBundle`] inserted as a component, or through
+/// [`DynBundleWorldExt`] or the command extensions, has been applied to it.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OnDynBundleApplied;
+
+/// Marker inserted by bundles built with [`DynBundle::mark_applied`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct DynBundleApplied;
+
+/// Query filter for entities whose [`DynBundle`] component has been inserted but not yet
+/// applied, because the command queued by its hook hasn't run.
+///
+/// Only bundles inserted as a component are covered. Bundles passed to
+/// [`DynBundleEntityCommandsExt::insert_dyn`] or [`DynBundleCommandsExt::spawn_dyn_batch`]
+/// stay inside their command until it runs, so nothing on the entity marks them as waiting.
+pub type PendingDynBundle = With<DynBundle>;
+
+/// Diagnostic system that warns about entities that were still [`PendingDynBundle`] the last
+/// time it ran and still are, which usually means they were inserted after the last command
+/// flush of the frame or by a system ordered after the one that should see them.
+pub fn warn_stale_pending_dyn_bundles(
+    pending: Query<Entity, PendingDynBundle>,
+    mut last_pending: Local<EntityHashSet>,
+) {
+    let mut current = EntityHashSet::default();
+    for entity in &pending {
+        if last_pending.contains(&entity) {
+            warn!("DynBundle on entity {entity:?} has been pending since the last run");
+        }
+        current.insert(entity);
+    }
+    *last_pending = current;
+}
 
 impl Command for DynBundleCommand {
     fn apply(self, world: &mut World) {
-        let Ok(mut entity_mut) = world.get_entity_mut(self.0) else {
-            #[cfg(debug_assertions)]
-            panic!("Entity with DynBundle component not found");
+        if let Err(reason) = self.try_apply(world) {
+            report_error(world, self.entity, reason);
+        }
+    }
+}
 
-            #[cfg(not(debug_assertions))]
-            return;
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DynBundleError {
+    EntityNotFound,
+    ComponentNotFound,
+    /// A reflection-backed operation was applied to a world without an `AppTypeRegistry`.
+    MissingTypeRegistry,
+    /// The type with this path isn't registered with `ReflectComponent`.
+    UnregisteredComponent(Cow<'static, str>),
+    /// A value passed to [`DynBundle::add_reflect`], here named by its own type path, doesn't
+    /// represent a concrete type.
+    MissingTypeInfo(Cow<'static, str>),
+    /// A bundle added with [`DynBundle::add_once`] was applied a second time.
+    AlreadyApplied,
+}
+
+impl fmt::Display for DynBundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynBundleError::EntityNotFound => f.write_str("entity not found"),
+            DynBundleError::ComponentNotFound => f.write_str("DynBundle component not found"),
+            DynBundleError::MissingTypeRegistry => {
+                f.write_str("AppTypeRegistry resource not found")
+            }
+            DynBundleError::UnregisteredComponent(type_path) => {
+                write!(f, "`{type_path}` is not registered with `ReflectComponent`")
+            }
+            DynBundleError::MissingTypeInfo(type_path) => {
+                write!(f, "reflected `{type_path}` value does not represent a type")
+            }
+            DynBundleError::AlreadyApplied => {
+                f.write_str("bundle added with `add_once` was already applied")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DynBundleError {}
+
+/// Controls how a [`DynBundle`] that could not be applied is reported.
+///
+/// Without this resource the [`DynBundleErrorPolicy::Warn`] policy is used.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DynBundleErrorPolicy {
+    Panic,
+    #[default]
+    Warn,
+    Ignore,
+    /// Sends a [`DynBundleFailed`] event, which has to be registered with `add_event`.
+    Event,
+}
+
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct DynBundleFailed {
+    pub entity: Entity,
+    pub reason: DynBundleError,
+}
+
+fn report_error(world: &mut World, entity: Entity, reason: DynBundleError) {
+    let policy = world
+        .get_resource::<DynBundleErrorPolicy>()
+        .copied()
+        .unwrap_or_default();
+    match policy {
+        DynBundleErrorPolicy::Panic => {
+            panic!("failed to apply DynBundle to entity {entity:?}: {reason}")
+        }
+        DynBundleErrorPolicy::Warn => {
+            warn!("failed to apply DynBundle to entity {entity:?}: {reason}")
+        }
+        DynBundleErrorPolicy::Ignore => {}
+        DynBundleErrorPolicy::Event => {
+            world.send_event(DynBundleFailed { entity, reason });
+        }
+    }
+}
+
+/// Reports an operation that failed while applying a bundle, so the remaining operations can
+/// still run.
+fn report_op_error(entity: &mut EntityWorldMut, reason: DynBundleError) {
+    let id = entity.id();
+    entity.world_scope(|world| report_error(world, id, reason));
+}
+
+/// A [`DynBundle`] that stays on the entity as its source of truth, instead of being removed
+/// once applied.
+///
+/// [`apply_dyn_bundle_sources`] applies it whenever it is inserted, replaced or mutated,
+/// using [`DynBundle::diff`] against the previously applied version, so components that
+/// the previous version added but the new one doesn't are removed.
+///
+/// Children from [`DynBundle::with_child`] and components from [`DynBundle::add_once`] are
+/// only applied by the first version that contains them, because applying them again would
+/// spawn the children again and find the once-only value already taken. Operations are told
+/// apart by identity, so a later version built by adding to a clone of the previous one
+/// keeps its children, while calling `with_child` again spawns another child. Children that
+/// a later version drops are not despawned. Closures and [`DynBundle::modify`] run again on
+/// each change, so they should be safe to repeat.
+#[derive(Component, Clone, Debug, Default)]
+pub struct DynBundleSource(pub DynBundle);
+
+/// The version of [`DynBundleSource`] that was last applied to the entity.
+#[derive(Component, Clone, Debug, Default)]
+pub struct AppliedDynBundleSource(pub DynBundle);
+
+/// Applies every [`DynBundleSource`] that changed since the last run.
+pub fn apply_dyn_bundle_sources(
+    mut commands: Commands,
+    sources: Query<
+        (Entity, &DynBundleSource, Option<&AppliedDynBundleSource>),
+        Changed<DynBundleSource>,
+    >,
+) {
+    for (entity, source, applied) in &sources {
+        let dyn_bundle = match applied {
+            Some(applied) => DynBundle::diff(&applied.0, &source.0).retain_ops(|op| {
+                !matches!(op.kind, OpKind::Child(_) | OpKind::InsertOnce(_))
+                    || !applied
+                        .0
+                        .ops
+                        .iter()
+                        .any(|applied| Arc::ptr_eq(&applied.apply, &op.apply))
+            }),
+            None => source.0.clone(),
         };
-        let Some(dyn_bundle) = entity_mut.take::<DynBundle>() else {
-            #[cfg(debug_assertions)]
-            panic!("DynBundle component not found on entity");
+        commands
+            .entity(entity)
+            .insert_dyn(dyn_bundle)
+            .try_insert(AppliedDynBundleSource(source.0.clone()));
+    }
+}
+
+/// The value of a component before [`DynBundle::apply_with_undo`] applied a bundle.
+enum Snapshot {
+    Value(Box<dyn PartialReflect>),
+    /// Not captured, because none of the bundle's operations touch the component.
+    Skipped,
+    /// Not captured, because the component isn't registered with `ReflectComponent`.
+    Unregistered,
+}
+
+fn children_of(entity: &EntityWorldMut) -> Vec<Entity> {
+    entity
+        .get::<Children>()
+        .map(|children| children.to_vec())
+        .unwrap_or_default()
+}
+
+/// Undo and redo history of bundles applied with [`DynBundle::apply_with_undo`].
+///
+/// The methods take the world separately, so the stack is usually accessed through
+/// `World::resource_scope` when stored as a resource.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct UndoStack {
+    undo: Vec<(Entity, DynBundle)>,
+    redo: Vec<(Entity, DynBundle)>,
+}
+
+impl UndoStack {
+    /// Applies `dyn_bundle` to `entity` and records its inverse, clearing the redo history.
+    pub fn apply(
+        &mut self,
+        world: &mut World,
+        entity: Entity,
+        dyn_bundle: impl IntoDynBundle,
+    ) -> Result<(), DynBundleError> {
+        let inverse = Self::apply_recorded(world, entity, &dyn_bundle.into_dynb())?;
+        self.undo.push((entity, inverse));
+        self.redo.clear();
+        Ok(())
+    }
+
+    /// Reverts the last applied or redone bundle. Returns `false` if there was nothing to undo.
+    ///
+    /// On error the history is left unchanged.
+    pub fn undo(&mut self, world: &mut World) -> Result<bool, DynBundleError> {
+        let Some((entity, inverse)) = self.undo.last() else {
+            return Ok(false);
+        };
+        let redo = Self::apply_recorded(world, *entity, inverse)?;
+        let (entity, _) = self.undo.pop().expect("entry was just applied");
+        self.redo.push((entity, redo));
+        Ok(true)
+    }
+
+    /// Re-applies the last undone bundle. Returns `false` if there was nothing to redo.
+    ///
+    /// On error the history is left unchanged.
+    pub fn redo(&mut self, world: &mut World) -> Result<bool, DynBundleError> {
+        let Some((entity, redo)) = self.redo.last() else {
+            return Ok(false);
+        };
+        let inverse = Self::apply_recorded(world, *entity, redo)?;
+        let (entity, _) = self.redo.pop().expect("entry was just applied");
+        self.undo.push((entity, inverse));
+        Ok(true)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
 
-            #[cfg(not(debug_assertions))]
-            return;
+    fn apply_recorded(
+        world: &mut World,
+        entity: Entity,
+        dyn_bundle: &DynBundle,
+    ) -> Result<DynBundle, DynBundleError> {
+        let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+            return Err(DynBundleError::EntityNotFound);
         };
-        dyn_bundle.apply(&mut entity_mut);
+        let inverse = dyn_bundle.apply_with_undo(&mut entity_mut);
+        world.trigger_targets(OnDynBundleApplied, entity);
+        Ok(inverse)
+    }
+}
+
+pub trait DynBundleWorldExt {
+    fn spawn_dyn(&mut self, dyn_bundle: impl IntoDynBundle) -> EntityWorldMut<'_>;
+
+    /// Spawns `count` entities with the same bundle, using
+    /// [`DynBundle::apply_batched_to`] for each of them.
+    ///
+    /// Bundle registration and the buffers for staged values are shared by the whole batch,
+    /// so only the first entity pays for them.
+    fn spawn_dyn_batch(&mut self, dyn_bundle: impl IntoDynBundle, count: usize) -> Vec<Entity>;
+}
+
+impl DynBundleWorldExt for World {
+    fn spawn_dyn(&mut self, dyn_bundle: impl IntoDynBundle) -> EntityWorldMut<'_> {
+        let mut entity_mut = self.spawn_empty();
+        dyn_bundle.into_dynb().apply_to(&mut entity_mut);
+        let entity = entity_mut.id();
+        self.trigger_targets(OnDynBundleApplied, entity);
+        self.entity_mut(entity)
+    }
+
+    fn spawn_dyn_batch(&mut self, dyn_bundle: impl IntoDynBundle, count: usize) -> Vec<Entity> {
+        let dyn_bundle = dyn_bundle.into_dynb();
+        let entities: Vec<Entity> = self.spawn_batch(std::iter::repeat_n((), count)).collect();
+        let mut staging = Staging::default();
+        for &entity in &entities {
+            dyn_bundle.apply_staged(&mut self.entity_mut(entity), &mut staging);
+            self.trigger_targets(OnDynBundleApplied, entity);
+        }
+        entities
+    }
+}
+
+pub trait DynBundleCommandsExt {
+    /// Reserves one entity per bundle and applies all of them in a single command, instead of
+    /// queueing a command per entity through the `DynBundle` component.
+    fn spawn_dyn_batch<I>(&mut self, iter: I) -> Vec<Entity>
+    where
+        I: IntoIterator,
+        I::Item: IntoDynBundle;
+}
+
+impl DynBundleCommandsExt for Commands<'_, '_> {
+    fn spawn_dyn_batch<I>(&mut self, iter: I) -> Vec<Entity>
+    where
+        I: IntoIterator,
+        I::Item: IntoDynBundle,
+    {
+        let batch: Vec<(Entity, DynBundle)> = iter
+            .into_iter()
+            .map(|dyn_bundle| (self.spawn_empty().id(), dyn_bundle.into_dynb()))
+            .collect();
+        let entities = batch.iter().map(|&(entity, _)| entity).collect();
+        self.queue(move |world: &mut World| {
+            let mut staging = Staging::default();
+            for (entity, dyn_bundle) in batch {
+                match world.get_entity_mut(entity) {
+                    Ok(mut entity_mut) => {
+                        dyn_bundle.apply_staged(&mut entity_mut, &mut staging);
+                        world.trigger_targets(OnDynBundleApplied, entity);
+                    }
+                    Err(_) => report_error(world, entity, DynBundleError::EntityNotFound),
+                }
+            }
+        });
+        entities
     }
 }
 
+pub trait DynBundleEntityCommandsExt {
+    fn insert_dyn(&mut self, dyn_bundle: impl IntoDynBundle) -> &mut Self;
+}
+
+impl DynBundleEntityCommandsExt for EntityCommands<'_> {
+    fn insert_dyn(&mut self, dyn_bundle: impl IntoDynBundle) -> &mut Self {
+        let dyn_bundle = dyn_bundle.into_dynb();
+        self.queue(
+            move |entity: Entity, world: &mut World| match world.get_entity_mut(entity) {
+                Ok(mut entity_mut) => {
+                    dyn_bundle.apply_to(&mut entity_mut);
+                    world.trigger_targets(OnDynBundleApplied, entity);
+                }
+                Err(_) => report_error(world, entity, DynBundleError::EntityNotFound),
+            },
+        )
+    }
+}
+
+/// Builds a [`DynBundle`] from a comma-separated list of bundles and builder calls.
+///
+/// Items are appended to a single accumulator from left to right, so a list of `n` items
+/// copies each operation once instead of once per enclosing item.
 #[macro_export]
 macro_rules! dynb {
     () => {
-        DynBundle::new()
+        $crate::DynBundle::new()
     };
 
-    ( $method:ident $( :: < $t:ty > )? ( $($args:tt)* ), $( $rest:tt )* ) => {{
-        dynb!().$method $( :: <$t> )? ( $($args)* ).append(dynb!($( $rest )*))
-    }};
+    (@acc $acc:expr; ) => {
+        $acc
+    };
 
-    ( $item:expr, $( $rest:tt )* ) => {{
-        dynb!().append($item).append(dynb!($( $rest )*))
-    }};
+    (@acc $acc:expr; if $cond:expr => { $( $then:tt )* } else { $( $else:tt )* } $( , $( $rest:tt )* )? ) => {
+        $crate::dynb!(@acc $acc.append_else($cond, $crate::dynb!($( $then )*), $crate::dynb!($( $else )*)); $( $( $rest )* )?)
+    };
 
-    ( $method:ident $( :: < $t:ty > )? ( $($args:tt)* ) ) => {{
-        dynb!().$method $( :: <$t> )? ( $($args)* )
-    }};
+    (@acc $acc:expr; if $cond:expr => { $( $then:tt )* } $( , $( $rest:tt )* )? ) => {
+        $crate::dynb!(@acc $acc.append_if($cond, $crate::dynb!($( $then )*)); $( $( $rest )* )?)
+    };
 
-    ( $item:expr ) => {{
-        dynb!().append($item)
-    }};
+    (@acc $acc:expr; if $cond:expr => $item:expr $( , $( $rest:tt )* )? ) => {
+        $crate::dynb!(@acc $acc.append_if($cond, $item); $( $( $rest )* )?)
+    };
+
+    (@acc $acc:expr; ? $opt:expr $( , $( $rest:tt )* )? ) => {
+        $crate::dynb!(@acc $acc.append_some($opt); $( $( $rest )* )?)
+    };
+
+    (@acc $acc:expr; $method:ident $( :: < $t:ty > )? ( $($args:tt)* ) $( , $( $rest:tt )* )? ) => {
+        $crate::dynb!(@acc $acc.append($crate::DynBundle::new().$method $( :: <$t> )? ( $($args)* )); $( $( $rest )* )?)
+    };
+
+    (@acc $acc:expr; $item:expr $( , $( $rest:tt )* )? ) => {
+        $crate::dynb!(@acc $acc.append($item); $( $( $rest )* )?)
+    };
+
+    ( $( $items:tt )+ ) => {
+        $crate::dynb!(@acc $crate::DynBundle::new(); $( $items )+)
+    };
 }
@@ -0,0 +1,258 @@
+use std::fmt;
+
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_reflect::{
+    serde::{TypedReflectDeserializer, TypedReflectSerializer},
+    PartialReflect, TypeRegistration, TypeRegistry,
+};
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeSeq, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{DynBundle, InsertMode, OpKind};
+
+const OP_STRUCT: &str = "DynBundleOp";
+const OP_FIELDS: &[&str] = &["op", "type_path", "value"];
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum OpTag {
+    Insert,
+    /// An insertion made with [`DynBundle::keep_existing`].
+    InsertIfNew,
+    Remove,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum OpField {
+    Op,
+    TypePath,
+    Value,
+}
+
+/// Serializes a [`DynBundle`] as an ordered list of `{ op, type_path, value }` entries.
+///
+/// Only operations created with [`DynBundle::add_reflect`] and [`DynBundle::del_by_type_path`]
+/// can be serialized; any other operation results in an error. Insertions that keep existing
+/// components use the `insert_if_new` op.
+pub struct DynBundleSerializer<'a> {
+    bundle: &'a DynBundle,
+    registry: &'a TypeRegistry,
+}
+
+impl<'a> DynBundleSerializer<'a> {
+    pub fn new(bundle: &'a DynBundle, registry: &'a TypeRegistry) -> Self {
+        DynBundleSerializer { bundle, registry }
+    }
+}
+
+impl Serialize for DynBundleSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.bundle.len()))?;
+        for op in self.bundle.visible_ops() {
+            seq.serialize_element(&OpSerializer {
+                kind: &op.kind,
+                mode: op.mode,
+                registry: self.registry,
+            })?;
+        }
+        seq.end()
+    }
+}
+
+struct OpSerializer<'a> {
+    kind: &'a OpKind,
+    mode: InsertMode,
+    registry: &'a TypeRegistry,
+}
+
+impl Serialize for OpSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.kind {
+            OpKind::InsertReflect(component) => {
+                let type_info = component.get_represented_type_info().ok_or_else(|| {
+                    ser::Error::custom(format!(
+                        "reflected `{}` value does not represent a type",
+                        component.reflect_type_path()
+                    ))
+                })?;
+                let op = match self.mode {
+                    InsertMode::Replace => OpTag::Insert,
+                    InsertMode::Keep => OpTag::InsertIfNew,
+                };
+                let mut state = serializer.serialize_struct(OP_STRUCT, 3)?;
+                state.serialize_field("op", &op)?;
+                state.serialize_field("type_path", type_info.type_path())?;
+                state.serialize_field(
+                    "value",
+                    &TypedReflectSerializer::new(&**component, self.registry),
+                )?;
+                state.end()
+            }
+            OpKind::RemoveReflect(type_path) => {
+                let mut state = serializer.serialize_struct(OP_STRUCT, 2)?;
+                state.serialize_field("op", &OpTag::Remove)?;
+                state.serialize_field("type_path", type_path)?;
+                state.end()
+            }
+            _ => Err(ser::Error::custom(
+                "only reflection-backed DynBundle ops can be serialized",
+            )),
+        }
+    }
+}
+
+/// Deserializes a [`DynBundle`] written by [`DynBundleSerializer`] against a type registry.
+pub struct DynBundleDeserializer<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'a> DynBundleDeserializer<'a> {
+    pub fn new(registry: &'a TypeRegistry) -> Self {
+        DynBundleDeserializer { registry }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for DynBundleDeserializer<'_> {
+    type Value = DynBundle;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<DynBundle, D::Error> {
+        deserializer.deserialize_seq(DynBundleVisitor {
+            registry: self.registry,
+        })
+    }
+}
+
+struct DynBundleVisitor<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> Visitor<'de> for DynBundleVisitor<'_> {
+    type Value = DynBundle;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of DynBundle ops")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DynBundle, A::Error> {
+        let mut bundle = DynBundle::new();
+        while let Some(op) = seq.next_element_seed(OpDeserializer {
+            registry: self.registry,
+        })? {
+            bundle = match op {
+                Op::Insert(component) => bundle.add_reflect(component),
+                Op::InsertIfNew(component) => {
+                    bundle.append(DynBundle::new().add_reflect(component).keep_existing())
+                }
+                Op::Remove(type_path) => bundle.del_by_type_path(&type_path),
+            };
+        }
+        Ok(bundle)
+    }
+}
+
+enum Op {
+    Insert(Box<dyn PartialReflect>),
+    InsertIfNew(Box<dyn PartialReflect>),
+    Remove(String),
+}
+
+struct OpDeserializer<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl OpDeserializer<'_> {
+    /// Checks that `type_path` names a component, so a typo fails when loading instead of when
+    /// the bundle is applied.
+    fn registration<E: de::Error>(&self, type_path: &str) -> Result<&TypeRegistration, E> {
+        let registration = self
+            .registry
+            .get_with_type_path(type_path)
+            .ok_or_else(|| E::custom(format!("no registration found for `{type_path}`")))?;
+        if registration.data::<ReflectComponent>().is_none() {
+            return Err(E::custom(format!(
+                "`{type_path}` is not registered with `ReflectComponent`"
+            )));
+        }
+        Ok(registration)
+    }
+
+    fn value_seed<E: de::Error>(&self, type_path: &str) -> Result<TypedReflectDeserializer<'_>, E> {
+        let registration = self.registration(type_path)?;
+        Ok(TypedReflectDeserializer::new(registration, self.registry))
+    }
+
+    fn remove<E: de::Error>(&self, type_path: String) -> Result<Op, E> {
+        self.registration::<E>(&type_path)?;
+        Ok(Op::Remove(type_path))
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for OpDeserializer<'_> {
+    type Value = Op;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Op, D::Error> {
+        deserializer.deserialize_struct(OP_STRUCT, OP_FIELDS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for OpDeserializer<'_> {
+    type Value = Op;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a DynBundle op")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Op, A::Error> {
+        let op = seq
+            .next_element::<OpTag>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let type_path = seq
+            .next_element::<String>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        match op {
+            OpTag::Insert | OpTag::InsertIfNew => {
+                let component = seq
+                    .next_element_seed(self.value_seed(&type_path)?)?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                Ok(match op {
+                    OpTag::InsertIfNew => Op::InsertIfNew(component),
+                    _ => Op::Insert(component),
+                })
+            }
+            OpTag::Remove => self.remove(type_path),
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Op, A::Error> {
+        let mut op = None;
+        let mut type_path: Option<String> = None;
+        let mut component = None;
+        while let Some(field) = map.next_key::<OpField>()? {
+            match field {
+                OpField::Op => op = Some(map.next_value::<OpTag>()?),
+                OpField::TypePath => type_path = Some(map.next_value()?),
+                OpField::Value => {
+                    let type_path = type_path
+                        .as_deref()
+                        .ok_or_else(|| de::Error::custom("`type_path` must come before `value`"))?;
+                    component = Some(map.next_value_seed(self.value_seed(type_path)?)?);
+                }
+            }
+        }
+        let op = op.ok_or_else(|| de::Error::missing_field("op"))?;
+        let type_path = type_path.ok_or_else(|| de::Error::missing_field("type_path"))?;
+        match op {
+            OpTag::Insert => component
+                .map(Op::Insert)
+                .ok_or_else(|| de::Error::missing_field("value")),
+            OpTag::InsertIfNew => component
+                .map(Op::InsertIfNew)
+                .ok_or_else(|| de::Error::missing_field("value")),
+            OpTag::Remove => self.remove(type_path),
+        }
+    }
+}
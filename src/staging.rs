@@ -0,0 +1,221 @@
+use std::{
+    alloc::{self, Layout},
+    any::TypeId,
+    mem,
+    ptr::{self, NonNull},
+    sync::Arc,
+};
+
+use bevy_ecs::{
+    bundle::DynamicBundle,
+    component::{ComponentId, ComponentInfo},
+    prelude::*,
+    ptr::OwningPtr,
+};
+use bevy_utils::TypeIdMap;
+
+use crate::InsertMode;
+
+/// Collects the net effect of consecutive insert and remove operations, so they can be
+/// applied to an entity with as few archetype moves as possible.
+///
+/// A `Staging` can be reused for several entities of the same world, in which case bundle
+/// registration and the buffers holding staged values are only set up for the first one.
+#[derive(Default)]
+pub(crate) struct Staging {
+    inserts: Vec<StagedComponent>,
+    removes: Vec<ComponentId>,
+    /// Component ids of every bundle type staged so far, in the order the bundle yields them.
+    bundle_ids: TypeIdMap<Arc<[ComponentId]>>,
+    /// Buffers of values that were moved into an entity, ready to hold the next ones.
+    free: Vec<Allocation>,
+}
+
+impl Staging {
+    pub(crate) fn insert<B: Bundle>(
+        &mut self,
+        entity: &mut EntityWorldMut,
+        mode: InsertMode,
+        bundle: B,
+    ) {
+        let component_ids = self.component_ids::<B>(entity);
+        let components = entity.world().components();
+        let mut component_ids = component_ids.iter().copied();
+        bundle.get_components(&mut |_, component| {
+            let component_id = component_ids
+                .next()
+                .expect("bundle should yield one value per component");
+            let info = components
+                .get_info(component_id)
+                .expect("bundle was registered");
+            let allocation = match self
+                .free
+                .iter()
+                .position(|allocation| allocation.layout == info.layout())
+            {
+                Some(index) => self.free.swap_remove(index),
+                None => Allocation::new(info.layout()),
+            };
+            // SAFETY: `component` holds a value of the component described by `info`, whose
+            // layout `allocation` was created with.
+            let staged = unsafe { StagedComponent::new(component_id, component, info, allocation) };
+
+            let exists = (entity.contains_id(component_id)
+                && !self.removes.contains(&component_id))
+                || self.inserts.iter().any(|staged| staged.id == component_id);
+            if mode == InsertMode::Keep && exists {
+                return;
+            }
+            self.removes.retain(|&removed| removed != component_id);
+            match self
+                .inserts
+                .iter_mut()
+                .find(|staged| staged.id == component_id)
+            {
+                Some(existing) => *existing = staged,
+                None => self.inserts.push(staged),
+            }
+        });
+    }
+
+    pub(crate) fn remove<B: Bundle>(&mut self, entity: &EntityWorldMut) {
+        B::get_component_ids(entity.world().components(), &mut |component_id| {
+            // Components that were never registered can't be on the entity.
+            let Some(component_id) = component_id else {
+                return;
+            };
+            self.inserts.retain(|staged| staged.id != component_id);
+            if !self.removes.contains(&component_id) {
+                self.removes.push(component_id);
+            }
+        });
+    }
+
+    pub(crate) fn flush(&mut self, entity: &mut EntityWorldMut) {
+        for component_id in self.removes.drain(..) {
+            if entity.contains_id(component_id) {
+                entity.remove_by_id(component_id);
+            }
+        }
+        if self.inserts.is_empty() {
+            return;
+        }
+
+        let component_ids: Vec<ComponentId> = self.inserts.iter().map(|staged| staged.id).collect();
+        // The values are moved into the entity below, so they must not be dropped here
+        // afterwards. Should the insertion panic, they are leaked instead.
+        for staged in &mut self.inserts {
+            staged.drop = None;
+        }
+        // SAFETY: every pointer holds an initialized value of the component with the matching
+        // id, and each id appears only once.
+        unsafe {
+            entity.insert_by_ids(
+                &component_ids,
+                self.inserts
+                    .iter()
+                    .map(|staged| OwningPtr::new(staged.allocation.data)),
+            );
+        }
+        self.free
+            .extend(self.inserts.drain(..).map(StagedComponent::into_allocation));
+    }
+
+    fn component_ids<B: Bundle>(&mut self, entity: &mut EntityWorldMut) -> Arc<[ComponentId]> {
+        if let Some(component_ids) = self.bundle_ids.get(&TypeId::of::<B>()) {
+            return component_ids.clone();
+        }
+        entity.world_scope(|world| {
+            world.register_bundle::<B>();
+        });
+        let mut component_ids = Vec::new();
+        B::get_component_ids(entity.world().components(), &mut |component_id| {
+            component_ids.push(component_id.expect("bundle was just registered"));
+        });
+        let component_ids: Arc<[ComponentId]> = component_ids.into();
+        self.bundle_ids
+            .insert(TypeId::of::<B>(), component_ids.clone());
+        component_ids
+    }
+}
+
+/// A buffer for a single component value.
+struct Allocation {
+    data: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Allocation {
+    fn new(layout: Layout) -> Self {
+        let data = if layout.size() == 0 {
+            NonNull::new(ptr::without_provenance_mut(layout.align()))
+                .expect("alignment is never zero")
+        } else {
+            // SAFETY: `layout` has a non-zero size.
+            let data = unsafe { alloc::alloc(layout) };
+            NonNull::new(data).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        };
+        Allocation { data, layout }
+    }
+}
+
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            // SAFETY: `data` was allocated with `layout`.
+            unsafe { alloc::dealloc(self.data.as_ptr(), self.layout) };
+        }
+    }
+}
+
+struct StagedComponent {
+    id: ComponentId,
+    allocation: Allocation,
+    /// Cleared once the value has been moved out of the allocation.
+    drop: Option<unsafe fn(OwningPtr<'_>)>,
+}
+
+impl StagedComponent {
+    /// # Safety
+    ///
+    /// `component` must point to a value of the component described by `info`, and
+    /// `allocation` must have been created with the layout of `info`.
+    unsafe fn new(
+        id: ComponentId,
+        component: OwningPtr<'_>,
+        info: &ComponentInfo,
+        allocation: Allocation,
+    ) -> Self {
+        // SAFETY: both pointers are valid for `layout.size()` bytes, and ownership of the value
+        // moves to `allocation`, so the caller doesn't drop it.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                component.as_ptr(),
+                allocation.data.as_ptr(),
+                allocation.layout.size(),
+            );
+        }
+        StagedComponent {
+            id,
+            allocation,
+            drop: info.drop(),
+        }
+    }
+
+    /// Returns the allocation of a value that was moved out, to be reused.
+    fn into_allocation(self) -> Allocation {
+        debug_assert!(self.drop.is_none(), "staged value was not moved out");
+        let mut this = mem::ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped again, so the allocation is moved out once.
+        unsafe { ptr::read(&this.allocation) }
+    }
+}
+
+impl Drop for StagedComponent {
+    fn drop(&mut self) {
+        if let Some(drop) = self.drop {
+            // SAFETY: `drop` is only set while the allocation holds an initialized value.
+            unsafe { drop(OwningPtr::new(self.allocation.data)) };
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use bevy_ecs::prelude::*;
+use dynamic_bundling::{
+    DynBundle, DynBundleApplied, DynBundleCommandsExt, DynBundleEntityCommandsExt,
+    DynBundleWorldExt, OnDynBundleApplied,
+};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct A(u32);
+
+/// What an `on_applied` callback saw of `A`.
+#[derive(Component, Debug, PartialEq)]
+struct Seen(Option<A>);
+
+/// Every `OnDynBundleApplied` trigger, with whether its entity had `A` at that point.
+#[derive(Resource, Default)]
+struct Triggered(Vec<(Entity, bool)>);
+
+fn world() -> World {
+    let mut world = World::new();
+    world.init_resource::<Triggered>();
+    world.add_observer(
+        |trigger: Trigger<OnDynBundleApplied>, a: Query<&A>, mut triggered: ResMut<Triggered>| {
+            let entity = trigger.entity();
+            triggered.0.push((entity, a.contains(entity)));
+        },
+    );
+    world
+}
+
+/// A bundle whose `on_applied` callback, added before `A`, records `A` and counts its runs.
+fn bundle(runs: &Arc<AtomicUsize>) -> DynBundle {
+    let runs = runs.clone();
+    DynBundle::new()
+        .on_applied(move |entity| {
+            runs.fetch_add(1, Ordering::SeqCst);
+            let a = entity.get::<A>().cloned();
+            entity.insert(Seen(a));
+        })
+        .add(A(1))
+}
+
+fn assert_applied_once(world: &World, entities: &[Entity], runs: &AtomicUsize) {
+    assert_eq!(runs.load(Ordering::SeqCst), entities.len());
+    for &entity in entities {
+        assert_eq!(world.get::<Seen>(entity), Some(&Seen(Some(A(1)))));
+    }
+    let expected: Vec<(Entity, bool)> = entities.iter().map(|&entity| (entity, true)).collect();
+    assert_eq!(world.resource::<Triggered>().0, expected);
+}
+
+#[test]
+fn spawn_dyn() {
+    let mut world = world();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let entity = world.spawn_dyn(bundle(&runs)).id();
+    assert_applied_once(&world, &[entity], &runs);
+}
+
+#[test]
+fn apply_to_runs_on_applied_without_triggering() {
+    let mut world = world();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let entity = world.spawn_empty().id();
+    bundle(&runs).apply_to(&mut world.entity_mut(entity));
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert_eq!(world.get::<Seen>(entity), Some(&Seen(Some(A(1)))));
+    assert!(world.resource::<Triggered>().0.is_empty());
+}
+
+#[test]
+fn insert_dyn() {
+    let mut world = world();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let entity = world.spawn_empty().id();
+    world.commands().entity(entity).insert_dyn(bundle(&runs));
+    world.flush();
+    assert_applied_once(&world, &[entity], &runs);
+}
+
+#[test]
+fn component_hook() {
+    let mut world = world();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let entity = world.commands().spawn(bundle(&runs)).id();
+    world.flush();
+    assert_applied_once(&world, &[entity], &runs);
+}
+
+#[test]
+fn world_spawn_dyn_batch() {
+    let mut world = world();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let entities = world.spawn_dyn_batch(bundle(&runs), 3);
+    assert_applied_once(&world, &entities, &runs);
+}
+
+#[test]
+fn commands_spawn_dyn_batch() {
+    let mut world = world();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let entities = world
+        .commands()
+        .spawn_dyn_batch([bundle(&runs), bundle(&runs), bundle(&runs)]);
+    world.flush();
+    assert_applied_once(&world, &entities, &runs);
+}
+
+#[test]
+fn mark_applied() {
+    let mut world = world();
+    let entity = world
+        .spawn_dyn(DynBundle::new().mark_applied().add(A(1)))
+        .id();
+    assert!(world.entity(entity).contains::<DynBundleApplied>());
+
+    let entity = world.spawn_dyn(A(1)).id();
+    assert!(!world.entity(entity).contains::<DynBundleApplied>());
+}
+
+#[test]
+fn mark_applied_after_every_operation() {
+    let mut world = world();
+    world.add_observer(
+        |trigger: Trigger<OnAdd, DynBundleApplied>, a: Query<&A>, mut commands: Commands| {
+            let a = a.get(trigger.entity()).ok().cloned();
+            commands.entity(trigger.entity()).insert(Seen(a));
+        },
+    );
+    let entity = world
+        .spawn_dyn(DynBundle::new().mark_applied().add(A(1)))
+        .id();
+    world.flush();
+    assert_eq!(world.get::<Seen>(entity), Some(&Seen(Some(A(1)))));
+}
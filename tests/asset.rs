@@ -0,0 +1,106 @@
+#![cfg(feature = "asset")]
+
+use std::{path::Path, thread, time::Duration};
+
+use bevy_app::App;
+use bevy_asset::{
+    io::{
+        memory::{Dir, MemoryAssetReader},
+        AssetSource, AssetSourceId,
+    },
+    AssetApp, AssetPlugin, AssetServer, Assets,
+};
+use bevy_core::TaskPoolPlugin;
+use bevy_ecs::{prelude::*, reflect::ReflectComponent};
+use bevy_reflect::{Reflect, TypePath};
+use dynamic_bundling::{
+    asset::{DynBundleAsset, DynBundleAssetPlugin, DynBundleHandle},
+    DynBundle,
+};
+
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component)]
+struct Health(u32);
+
+/// Builds an app whose default asset source is an in-memory directory holding `door.dynb.ron`.
+fn app() -> App {
+    let dir = Dir::default();
+    dir.insert_asset_text(
+        Path::new("door.dynb.ron"),
+        &format!(
+            r#"[(op: insert, type_path: "{}", value: (10))]"#,
+            Health::type_path()
+        ),
+    );
+
+    let mut app = App::new();
+    app.register_asset_source(
+        AssetSourceId::Default,
+        AssetSource::build().with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+    )
+    .add_plugins((
+        TaskPoolPlugin::default(),
+        AssetPlugin::default(),
+        DynBundleAssetPlugin,
+    ))
+    .register_type::<Health>();
+    app
+}
+
+/// Updates `app` until `done` returns true, since assets are loaded on another thread.
+fn update_until(app: &mut App, mut done: impl FnMut(&World) -> bool) {
+    for _ in 0..200 {
+        app.update();
+        if done(app.world()) {
+            return;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    panic!("the DynBundle asset was not applied in time");
+}
+
+fn spawn_door(app: &mut App) -> Entity {
+    let handle = app
+        .world()
+        .resource::<AssetServer>()
+        .load::<DynBundleAsset>("door.dynb.ron");
+    app.world_mut().spawn(DynBundleHandle(handle)).id()
+}
+
+#[test]
+fn applies_loaded_asset() {
+    let mut app = app();
+    let entity = spawn_door(&mut app);
+
+    update_until(&mut app, |world| world.get::<Health>(entity).is_some());
+    assert_eq!(app.world().get::<Health>(entity), Some(&Health(10)));
+}
+
+#[test]
+fn reapplies_modified_asset() {
+    let mut app = app();
+    let entity = spawn_door(&mut app);
+    update_until(&mut app, |world| world.get::<Health>(entity).is_some());
+
+    // Entities spawned after the asset is loaded get it applied too.
+    let late = spawn_door(&mut app);
+    update_until(&mut app, |world| world.get::<Health>(late).is_some());
+
+    let handle = app
+        .world()
+        .get::<DynBundleHandle>(entity)
+        .unwrap()
+        .0
+        .clone();
+    app.world_mut()
+        .resource_mut::<Assets<DynBundleAsset>>()
+        .get_mut(&handle)
+        .unwrap()
+        .0 = DynBundle::new().add(Health(20));
+
+    update_until(&mut app, |world| {
+        [entity, late]
+            .iter()
+            .all(|&entity| world.get::<Health>(entity) == Some(&Health(20)))
+    });
+}
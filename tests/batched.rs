@@ -0,0 +1,275 @@
+//! Checks that `apply_batched_to`, which moves component values through raw staging buffers,
+//! drops every value exactly once. Run under Miri with `cargo +nightly miri test --test batched`.
+
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicIsize, Ordering},
+        Arc,
+    },
+};
+
+use bevy_ecs::prelude::*;
+use dynamic_bundling::{DynBundle, DynBundleWorldExt};
+use proptest::{collection::vec, prelude::*};
+
+/// Counts its live instances, so leaks and double drops show up as a wrong count.
+#[derive(Component, Debug)]
+struct Tracked {
+    value: u32,
+    live: Arc<AtomicIsize>,
+}
+
+impl Tracked {
+    fn new(value: u32, live: &Arc<AtomicIsize>) -> Self {
+        live.fetch_add(1, Ordering::SeqCst);
+        Tracked {
+            value,
+            live: live.clone(),
+        }
+    }
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        Tracked::new(self.value, &self.live)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.live.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+thread_local! {
+    static ZST_LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+#[derive(Component, Debug)]
+struct TrackedZst;
+
+impl TrackedZst {
+    fn new() -> Self {
+        ZST_LIVE.set(ZST_LIVE.get() + 1);
+        TrackedZst
+    }
+}
+
+impl Clone for TrackedZst {
+    fn clone(&self) -> Self {
+        TrackedZst::new()
+    }
+}
+
+impl Drop for TrackedZst {
+    fn drop(&mut self) {
+        ZST_LIVE.set(ZST_LIVE.get() - 1);
+    }
+}
+
+#[derive(Component, Clone, Debug, PartialEq)]
+#[repr(align(64))]
+struct Aligned([u8; 64]);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Plain(u8);
+
+fn live(live: &Arc<AtomicIsize>) -> isize {
+    live.load(Ordering::SeqCst)
+}
+
+#[test]
+fn replaced_value_is_dropped_once() {
+    let counter = Arc::new(AtomicIsize::new(0));
+    let dyn_bundle = DynBundle::new()
+        .add(Tracked::new(1, &counter))
+        .add(Tracked::new(2, &counter));
+
+    let mut world = World::new();
+    let entity = world.spawn_empty().id();
+    dyn_bundle.apply_batched_to(&mut world.entity_mut(entity));
+    assert_eq!(world.get::<Tracked>(entity).map(|t| t.value), Some(2));
+    // Two values held by the bundle, one on the entity.
+    assert_eq!(live(&counter), 3);
+
+    drop(dyn_bundle);
+    drop(world);
+    assert_eq!(live(&counter), 0);
+}
+
+#[test]
+fn kept_value_is_dropped_once() {
+    let counter = Arc::new(AtomicIsize::new(0));
+    let mut world = World::new();
+    let entity = world.spawn(Tracked::new(0, &counter)).id();
+
+    let dyn_bundle = DynBundle::new().add_if_new(Tracked::new(1, &counter));
+    dyn_bundle.apply_batched_to(&mut world.entity_mut(entity));
+    assert_eq!(world.get::<Tracked>(entity).map(|t| t.value), Some(0));
+    assert_eq!(live(&counter), 2);
+
+    drop(dyn_bundle);
+    drop(world);
+    assert_eq!(live(&counter), 0);
+}
+
+#[test]
+fn removed_value_is_dropped_once() {
+    let counter = Arc::new(AtomicIsize::new(0));
+    let mut world = World::new();
+    let entity = world.spawn(Tracked::new(0, &counter)).id();
+
+    let dyn_bundle = DynBundle::new()
+        .add(Tracked::new(1, &counter))
+        .del::<Tracked>()
+        .add(Plain(1));
+    dyn_bundle.apply_batched_to(&mut world.entity_mut(entity));
+    assert!(!world.entity(entity).contains::<Tracked>());
+    assert_eq!(world.get::<Plain>(entity), Some(&Plain(1)));
+    assert_eq!(live(&counter), 1);
+
+    drop(dyn_bundle);
+    drop(world);
+    assert_eq!(live(&counter), 0);
+}
+
+#[test]
+fn values_staged_before_a_closure_are_flushed() {
+    let counter = Arc::new(AtomicIsize::new(0));
+    let dyn_bundle = DynBundle::new()
+        .add(Tracked::new(1, &counter))
+        .with(|entity| assert!(entity.contains::<Tracked>()))
+        .add(Tracked::new(2, &counter));
+
+    let mut world = World::new();
+    let entity = world.spawn_empty().id();
+    dyn_bundle.apply_batched_to(&mut world.entity_mut(entity));
+    assert_eq!(world.get::<Tracked>(entity).map(|t| t.value), Some(2));
+
+    drop(dyn_bundle);
+    drop(world);
+    assert_eq!(live(&counter), 0);
+}
+
+#[test]
+fn zero_sized_values() {
+    let dyn_bundle = DynBundle::new()
+        .add(TrackedZst::new())
+        .add(TrackedZst::new())
+        .add_if_new(TrackedZst::new());
+
+    let mut world = World::new();
+    let entity = world.spawn_empty().id();
+    dyn_bundle.apply_batched_to(&mut world.entity_mut(entity));
+    assert!(world.entity(entity).contains::<TrackedZst>());
+    assert_eq!(ZST_LIVE.get(), 4);
+
+    drop(dyn_bundle);
+    drop(world);
+    assert_eq!(ZST_LIVE.get(), 0);
+}
+
+#[test]
+fn over_aligned_values() {
+    let dyn_bundle = DynBundle::new()
+        .add(Aligned([1; 64]))
+        .add(Plain(1))
+        .add(Aligned([2; 64]));
+
+    let mut world = World::new();
+    let entity = world.spawn_empty().id();
+    dyn_bundle.apply_batched_to(&mut world.entity_mut(entity));
+    assert_eq!(world.get::<Aligned>(entity), Some(&Aligned([2; 64])));
+    assert_eq!(world.get::<Plain>(entity), Some(&Plain(1)));
+}
+
+#[test]
+fn staging_is_reused_across_a_batch() {
+    let counter = Arc::new(AtomicIsize::new(0));
+    let dyn_bundle = DynBundle::new()
+        .add(Tracked::new(1, &counter))
+        .add(Aligned([1; 64]))
+        .add(TrackedZst::new())
+        .del::<TrackedZst>()
+        .add(Plain(1));
+
+    let mut world = World::new();
+    let entities = world.spawn_dyn_batch(dyn_bundle.clone(), 16);
+    for &entity in &entities {
+        assert_eq!(world.get::<Tracked>(entity).map(|t| t.value), Some(1));
+        assert_eq!(world.get::<Aligned>(entity), Some(&Aligned([1; 64])));
+        assert_eq!(world.get::<Plain>(entity), Some(&Plain(1)));
+        assert!(!world.entity(entity).contains::<TrackedZst>());
+    }
+    // Two values held by the bundle and its clone, one per entity.
+    assert_eq!(live(&counter), 2 + 16);
+
+    drop(dyn_bundle);
+    drop(world);
+    assert_eq!(live(&counter), 0);
+    assert_eq!(ZST_LIVE.get(), 0);
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    Add(u8),
+    AddIfNew(u8),
+    Del,
+    AddAligned(u8),
+    DelAligned,
+    Closure,
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        any::<u8>().prop_map(Step::Add),
+        any::<u8>().prop_map(Step::AddIfNew),
+        Just(Step::Del),
+        any::<u8>().prop_map(Step::AddAligned),
+        Just(Step::DelAligned),
+        Just(Step::Closure),
+    ]
+}
+
+fn build(steps: &[Step]) -> DynBundle {
+    steps
+        .iter()
+        .fold(DynBundle::new(), |acc, step| match *step {
+            Step::Add(value) => acc.add(Plain(value)),
+            Step::AddIfNew(value) => acc.add_if_new(Plain(value)),
+            Step::Del => acc.del::<Plain>(),
+            Step::AddAligned(value) => acc.add(Aligned([value; 64])),
+            Step::DelAligned => acc.del::<Aligned>(),
+            Step::Closure => acc.with(|entity| {
+                if let Some(mut plain) = entity.get_mut::<Plain>() {
+                    plain.0 = plain.0.wrapping_add(1);
+                }
+            }),
+        })
+}
+
+fn observe(world: &World, entity: Entity) -> (Option<Plain>, Option<Aligned>) {
+    (
+        world.get::<Plain>(entity).cloned(),
+        world.get::<Aligned>(entity).cloned(),
+    )
+}
+
+proptest! {
+    #[test]
+    fn batched_matches_apply_to(initial in any::<Option<u8>>(), steps in vec(step(), 0..24)) {
+        let dyn_bundle = build(&steps);
+        let mut world = World::new();
+        let entities = [world.spawn_empty().id(), world.spawn_empty().id()];
+        if let Some(value) = initial {
+            for entity in entities {
+                world.entity_mut(entity).insert(Plain(value));
+            }
+        }
+
+        dyn_bundle.apply_to(&mut world.entity_mut(entities[0]));
+        dyn_bundle.apply_batched_to(&mut world.entity_mut(entities[1]));
+        prop_assert_eq!(observe(&world, entities[0]), observe(&world, entities[1]));
+    }
+}
@@ -0,0 +1,74 @@
+use bevy_ecs::prelude::*;
+use dynamic_bundling::{DynBundle, DynBundleWorldExt};
+
+#[derive(Resource)]
+struct Spawned(Vec<Entity>);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Health(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct MaxHealth(u32);
+
+#[test]
+fn with_sees_earlier_operations() {
+    let mut world = World::new();
+    let entity = world.spawn_dyn(DynBundle::new().add(MaxHealth(30)).with(|entity| {
+        let max = entity.get::<MaxHealth>().unwrap().0;
+        entity.insert(Health(max));
+    }));
+    assert_eq!(entity.get::<Health>(), Some(&Health(30)));
+}
+
+#[test]
+fn with_world_gets_the_world_and_the_entity() {
+    let mut world = World::new();
+    world.insert_resource(Spawned(Vec::new()));
+    let dyn_bundle = DynBundle::new()
+        .add(Health(1))
+        .with_world(|world, entity| {
+            assert!(world.entity(entity).contains::<Health>());
+            world.resource_mut::<Spawned>().0.push(entity);
+            world.entity_mut(entity).insert(MaxHealth(2));
+        })
+        .modify::<MaxHealth>(|max| max.0 *= 10);
+
+    let first = world.spawn_dyn(dyn_bundle.clone()).id();
+    let second = world.spawn_dyn(dyn_bundle).id();
+    assert_eq!(world.resource::<Spawned>().0, [first, second]);
+    assert_eq!(world.get::<MaxHealth>(first), Some(&MaxHealth(20)));
+}
+
+#[test]
+fn modify_changes_an_existing_component() {
+    let mut world = World::new();
+    let entity = world.spawn(Health(5)).id();
+    DynBundle::new()
+        .modify::<Health>(|health| health.0 += 1)
+        .modify::<Health>(|health| health.0 *= 2)
+        .apply_to(&mut world.entity_mut(entity));
+    assert_eq!(world.get::<Health>(entity), Some(&Health(12)));
+}
+
+#[test]
+fn modify_sees_components_added_before_it() {
+    let mut world = World::new();
+    let entity = world.spawn_dyn(
+        DynBundle::new()
+            .add(Health(5))
+            .modify::<Health>(|health| health.0 += 1),
+    );
+    assert_eq!(entity.get::<Health>(), Some(&Health(6)));
+}
+
+#[test]
+fn modify_on_a_missing_component_does_nothing() {
+    let mut world = World::new();
+    let entity = world.spawn_dyn(
+        DynBundle::new()
+            .modify::<Health>(|health| health.0 += 1)
+            .add(MaxHealth(1)),
+    );
+    assert!(!entity.contains::<Health>());
+    assert_eq!(entity.get::<MaxHealth>(), Some(&MaxHealth(1)));
+}
@@ -0,0 +1,123 @@
+use bevy_ecs::prelude::*;
+use dynamic_bundling::{dynb, DynBundle, DynBundleWorldExt};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct A;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct B;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct C;
+
+/// Spawns an entity from `dyn_bundle` and returns which of `A`, `B` and `C` it ended up with.
+fn components(dyn_bundle: DynBundle) -> (bool, bool, bool) {
+    let mut world = World::new();
+    let entity = world.spawn_dyn(dyn_bundle);
+    (
+        entity.contains::<A>(),
+        entity.contains::<B>(),
+        entity.contains::<C>(),
+    )
+}
+
+#[test]
+fn append_if() {
+    assert_eq!(
+        components(DynBundle::new_add(A).append_if(true, B)),
+        (true, true, false)
+    );
+    assert_eq!(
+        components(DynBundle::new_add(A).append_if(false, B)),
+        (true, false, false)
+    );
+}
+
+#[test]
+fn append_some() {
+    assert_eq!(
+        components(DynBundle::new_add(A).append_some(Some(B))),
+        (true, true, false)
+    );
+    assert_eq!(
+        components(DynBundle::new_add(A).append_some(None::<B>)),
+        (true, false, false)
+    );
+}
+
+#[test]
+fn append_with() {
+    assert_eq!(
+        components(DynBundle::new_add(A).append_with(|| Some(B))),
+        (true, true, false)
+    );
+    assert_eq!(
+        components(DynBundle::new_add(A).append_with(|| None::<B>)),
+        (true, false, false)
+    );
+}
+
+#[test]
+fn append_else() {
+    assert_eq!(
+        components(DynBundle::new_add(A).append_else(true, B, C)),
+        (true, true, false)
+    );
+    assert_eq!(
+        components(DynBundle::new_add(A).append_else(false, B, C)),
+        (true, false, true)
+    );
+}
+
+#[test]
+fn macro_if() {
+    assert_eq!(components(dynb!(A, if true => B)), (true, true, false));
+    assert_eq!(components(dynb!(A, if false => B)), (true, false, false));
+    assert_eq!(components(dynb!(if true => B, C)), (false, true, true));
+    assert_eq!(components(dynb!(if false => B, C)), (false, false, true));
+}
+
+#[test]
+fn macro_if_block() {
+    assert_eq!(components(dynb!(if true => { A, B })), (true, true, false));
+    assert_eq!(
+        components(dynb!(if false => { A, B })),
+        (false, false, false)
+    );
+    assert_eq!(
+        components(dynb!(if true => { A, B }, C)),
+        (true, true, true)
+    );
+    assert_eq!(
+        components(dynb!(if false => { A, B }, C)),
+        (false, false, true)
+    );
+}
+
+#[test]
+fn macro_if_else() {
+    assert_eq!(
+        components(dynb!(if true => { A } else { B })),
+        (true, false, false)
+    );
+    assert_eq!(
+        components(dynb!(if false => { A } else { B })),
+        (false, true, false)
+    );
+    assert_eq!(
+        components(dynb!(if true => { A } else { B }, C)),
+        (true, false, true)
+    );
+    assert_eq!(
+        components(dynb!(if false => { A } else { B }, C)),
+        (false, true, true)
+    );
+}
+
+#[test]
+fn macro_option() {
+    assert_eq!(components(dynb!(A, ?Some(B))), (true, true, false));
+    assert_eq!(components(dynb!(A, ?None::<B>)), (true, false, false));
+    assert_eq!(components(dynb!(?Some(B), C)), (false, true, true));
+    assert_eq!(components(dynb!(?None::<B>, C)), (false, false, true));
+}
@@ -0,0 +1,99 @@
+use bevy_ecs::prelude::*;
+use dynamic_bundling::{DynBundle, DynBundleWorldExt};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct A(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct B;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct C;
+
+/// Spawns an entity from `old`, applies the diff to `new` and returns the entity's components.
+fn reconfigure(old: &DynBundle, new: &DynBundle) -> (Option<A>, bool, bool) {
+    let mut world = World::new();
+    let mut entity = world.spawn_dyn(old.clone());
+    DynBundle::diff(old, new).apply_to(&mut entity);
+    (
+        entity.get::<A>().cloned(),
+        entity.contains::<B>(),
+        entity.contains::<C>(),
+    )
+}
+
+/// The components of an entity spawned from `new` directly, which a diff has to reproduce.
+fn spawn(new: &DynBundle) -> (Option<A>, bool, bool) {
+    let mut world = World::new();
+    let entity = world.spawn_dyn(new.clone());
+    (
+        entity.get::<A>().cloned(),
+        entity.contains::<B>(),
+        entity.contains::<C>(),
+    )
+}
+
+#[test]
+fn removes_components_dropped_by_new() {
+    let old = DynBundle::new().add((A(1), B)).add(C);
+    let new = DynBundle::new().add(A(2));
+    assert_eq!(reconfigure(&old, &new), (Some(A(2)), false, false));
+}
+
+#[test]
+fn keeps_removal_after_kept_insert() {
+    let dyn_bundle = DynBundle::new().add((A(1), B)).del::<B>();
+    assert_eq!(
+        reconfigure(&dyn_bundle, &dyn_bundle),
+        (Some(A(1)), false, false)
+    );
+    assert_eq!(reconfigure(&dyn_bundle, &dyn_bundle), spawn(&dyn_bundle));
+}
+
+#[test]
+fn keeps_removal_after_closure() {
+    let old = DynBundle::new();
+    let new = DynBundle::new()
+        .with(|entity| {
+            entity.insert(A(1));
+        })
+        .del::<A>();
+    assert_eq!(reconfigure(&old, &new), (None, false, false));
+    assert_eq!(reconfigure(&old, &new), spawn(&new));
+}
+
+#[test]
+fn removes_before_closures() {
+    let old = DynBundle::new().add(A(1));
+    let new = DynBundle::new().del::<A>().with(|entity| {
+        entity.insert(A(2));
+    });
+    assert_eq!(reconfigure(&old, &new), (Some(A(2)), false, false));
+    assert_eq!(reconfigure(&old, &new), spawn(&new));
+}
+
+#[test]
+fn matches_applying_new() {
+    let bundles = [
+        DynBundle::new(),
+        DynBundle::new().add(A(1)),
+        DynBundle::new().add((A(1), B)).del::<B>(),
+        DynBundle::new().add(B).add(C).del::<B>(),
+        DynBundle::new().del::<A>().add(C),
+        DynBundle::new().add(A(3)).del::<C>(),
+        DynBundle::new()
+            .with(|entity| {
+                entity.insert(A(1));
+            })
+            .del::<A>(),
+    ];
+    for old in &bundles {
+        for new in &bundles {
+            assert_eq!(
+                reconfigure(old, new),
+                spawn(new),
+                "diff from {old:?} to {new:?}"
+            );
+        }
+    }
+}
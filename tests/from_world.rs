@@ -0,0 +1,78 @@
+use bevy_ecs::prelude::*;
+use dynamic_bundling::DynBundle;
+
+#[derive(Resource)]
+struct Scale(u32);
+
+#[derive(Component, Debug, PartialEq)]
+struct Size(u32);
+
+#[derive(Component, Debug, PartialEq)]
+struct Owner(Entity);
+
+#[derive(Component, Debug, Default, PartialEq)]
+struct Counter(u32);
+
+#[derive(Component, Debug, PartialEq)]
+struct Scaled(u32);
+
+impl FromWorld for Scaled {
+    fn from_world(world: &mut World) -> Self {
+        Scaled(world.resource::<Scale>().0 * 10)
+    }
+}
+
+/// Spawns `dyn_bundle` as a component through `Commands`, changes `Scale` to 5 and then
+/// applies the queued commands, so values built from the world see the new scale.
+fn spawn_through_hook(dyn_bundle: DynBundle) -> (World, Entity) {
+    let mut world = World::new();
+    world.insert_resource(Scale(1));
+    let entity = world.commands().spawn(dyn_bundle).id();
+    world.resource_mut::<Scale>().0 = 5;
+    world.flush();
+    (world, entity)
+}
+
+#[test]
+fn add_from_world_reads_resources_and_the_entity() {
+    let dyn_bundle = DynBundle::new()
+        .add_from_world(|world, entity| (Size(world.resource::<Scale>().0), Owner(entity)));
+    let (world, entity) = spawn_through_hook(dyn_bundle);
+    assert_eq!(world.get::<Size>(entity), Some(&Size(5)));
+    assert_eq!(world.get::<Owner>(entity), Some(&Owner(entity)));
+    assert!(!world.entity(entity).contains::<DynBundle>());
+}
+
+#[test]
+fn add_from_world_runs_per_application() {
+    let dyn_bundle = DynBundle::new().add_from_world(|_, entity| Owner(entity));
+    let mut world = World::new();
+    let first = world.spawn(dyn_bundle.clone()).id();
+    let second = world.spawn(dyn_bundle).id();
+    world.flush();
+    assert_eq!(world.get::<Owner>(first), Some(&Owner(first)));
+    assert_eq!(world.get::<Owner>(second), Some(&Owner(second)));
+}
+
+#[test]
+fn add_default() {
+    let (world, entity) = spawn_through_hook(DynBundle::new().add_default::<Counter>());
+    assert_eq!(world.get::<Counter>(entity), Some(&Counter(0)));
+}
+
+#[test]
+fn add_default_keeps_existing_value() {
+    let mut world = World::new();
+    let entity = world.spawn(Counter(3)).id();
+    world
+        .entity_mut(entity)
+        .insert(DynBundle::new().add_default::<Counter>().keep_existing());
+    world.flush();
+    assert_eq!(world.get::<Counter>(entity), Some(&Counter(3)));
+}
+
+#[test]
+fn add_from_world_default() {
+    let (world, entity) = spawn_through_hook(DynBundle::new().add_from_world_default::<Scaled>());
+    assert_eq!(world.get::<Scaled>(entity), Some(&Scaled(50)));
+}
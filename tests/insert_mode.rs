@@ -0,0 +1,90 @@
+use bevy_ecs::prelude::*;
+use dynamic_bundling::{DynBundle, OpInfo};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct A(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct B(u32);
+
+/// Applies `dyn_bundle` to an entity that starts with `A(1)`.
+fn apply_to_existing(dyn_bundle: DynBundle) -> (Option<A>, Option<B>) {
+    let mut world = World::new();
+    let mut entity = world.spawn(A(1));
+    dyn_bundle.apply_to(&mut entity);
+    (entity.get::<A>().cloned(), entity.get::<B>().cloned())
+}
+
+/// Applies `dyn_bundle` to an empty entity.
+fn apply_to_empty(dyn_bundle: DynBundle) -> (Option<A>, Option<B>) {
+    let mut world = World::new();
+    let mut entity = world.spawn_empty();
+    dyn_bundle.apply_to(&mut entity);
+    (entity.get::<A>().cloned(), entity.get::<B>().cloned())
+}
+
+#[test]
+fn add_overwrites() {
+    let dyn_bundle = DynBundle::new().add(A(2)).add(B(2));
+    assert_eq!(apply_to_existing(dyn_bundle), (Some(A(2)), Some(B(2))));
+}
+
+#[test]
+fn add_if_new_keeps_existing_values() {
+    let dyn_bundle = DynBundle::new().add_if_new(A(2)).add_if_new(B(2));
+    assert_eq!(
+        apply_to_existing(dyn_bundle.clone()),
+        (Some(A(1)), Some(B(2)))
+    );
+    assert_eq!(apply_to_empty(dyn_bundle), (Some(A(2)), Some(B(2))));
+}
+
+#[test]
+fn add_if_new_after_add_in_the_same_bundle() {
+    let dyn_bundle = DynBundle::new().add(B(3)).add_if_new(B(2));
+    assert_eq!(apply_to_empty(dyn_bundle), (None, Some(B(3))));
+}
+
+#[test]
+fn keep_existing_covers_earlier_operations_only() {
+    let dyn_bundle = DynBundle::new().add(A(2)).keep_existing().add(B(2));
+    assert_eq!(apply_to_existing(dyn_bundle), (Some(A(1)), Some(B(2))));
+
+    let dyn_bundle = DynBundle::new().add(A(2)).keep_existing().add(A(3));
+    assert_eq!(apply_to_existing(dyn_bundle), (Some(A(3)), None));
+}
+
+#[test]
+fn add_or_modify() {
+    let dyn_bundle = DynBundle::new().add_or_modify(A(10), |a| a.0 += 5);
+    assert_eq!(apply_to_existing(dyn_bundle.clone()), (Some(A(6)), None));
+    assert_eq!(apply_to_empty(dyn_bundle), (Some(A(10)), None));
+}
+
+#[test]
+fn add_or_modify_with_keep_existing() {
+    let dyn_bundle = DynBundle::new()
+        .add_or_modify(A(10), |a| a.0 += 5)
+        .keep_existing();
+    assert_eq!(apply_to_existing(dyn_bundle.clone()), (Some(A(1)), None));
+    assert_eq!(apply_to_empty(dyn_bundle), (Some(A(10)), None));
+}
+
+#[test]
+fn ops_report_the_insert_mode() {
+    let dyn_bundle = DynBundle::new().add(A(1)).add_or_modify(B(1), |b| b.0 += 1);
+    assert!(matches!(
+        dyn_bundle.ops()[..],
+        [OpInfo::Insert(_), OpInfo::Insert(_)]
+    ));
+
+    let dyn_bundle = dyn_bundle.keep_existing().add_if_new(A(2));
+    assert!(matches!(
+        dyn_bundle.ops()[..],
+        [
+            OpInfo::InsertIfNew(_),
+            OpInfo::InsertIfNew(_),
+            OpInfo::InsertIfNew(_)
+        ]
+    ));
+}
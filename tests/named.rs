@@ -0,0 +1,99 @@
+use std::any::TypeId;
+
+use bevy_ecs::prelude::*;
+use dynamic_bundling::{DynBundle, DynBundleWorldExt, OpInfo};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct A;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct B;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct C;
+
+/// The type of the single component inserted by each operation.
+fn inserted(dyn_bundle: &DynBundle) -> Vec<TypeId> {
+    dyn_bundle
+        .ops()
+        .iter()
+        .map(|info| match info {
+            OpInfo::Insert(components) => components[0].type_id().unwrap(),
+            info => panic!("unexpected op {info:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn replace_keeps_position() {
+    let dyn_bundle = DynBundle::new()
+        .add(A)
+        .add_named("slot", B)
+        .add(C)
+        .replace_named("slot", A);
+    assert_eq!(
+        inserted(&dyn_bundle),
+        [TypeId::of::<A>(), TypeId::of::<A>(), TypeId::of::<C>()]
+    );
+}
+
+#[test]
+fn empty_group_keeps_its_name() {
+    let dyn_bundle = DynBundle::new()
+        .add(A)
+        .add_named("slot", DynBundle::new())
+        .add(C);
+    assert_eq!(dyn_bundle.len(), 2);
+
+    let dyn_bundle = dyn_bundle.replace_named("slot", B);
+    assert_eq!(
+        inserted(&dyn_bundle),
+        [TypeId::of::<A>(), TypeId::of::<B>(), TypeId::of::<C>()]
+    );
+
+    // Emptying the group again keeps it replaceable.
+    let dyn_bundle = dyn_bundle
+        .replace_named("slot", DynBundle::new())
+        .replace_named("slot", A);
+    assert_eq!(
+        inserted(&dyn_bundle),
+        [TypeId::of::<A>(), TypeId::of::<A>(), TypeId::of::<C>()]
+    );
+}
+
+#[test]
+fn nested_names_are_kept() {
+    let inner = DynBundle::new().add_named("inner", A).add(B);
+    let dyn_bundle = DynBundle::new().add_named("outer", inner).add(C);
+
+    let replaced = dyn_bundle.clone().replace_named("inner", C);
+    assert_eq!(
+        inserted(&replaced),
+        [TypeId::of::<C>(), TypeId::of::<B>(), TypeId::of::<C>()]
+    );
+
+    // The replacement is still part of the outer group.
+    let removed = replaced.remove_named("outer");
+    assert_eq!(inserted(&removed), [TypeId::of::<C>()]);
+
+    let removed = dyn_bundle.remove_named("inner");
+    assert_eq!(inserted(&removed), [TypeId::of::<B>(), TypeId::of::<C>()]);
+}
+
+#[test]
+fn missing_name_is_ignored() {
+    let dyn_bundle = DynBundle::new().add(A).replace_named("slot", B);
+    assert_eq!(inserted(&dyn_bundle), [TypeId::of::<A>()]);
+}
+
+#[test]
+fn groups_apply_in_place() {
+    let dyn_bundle = DynBundle::new()
+        .add_named("slot", DynBundle::new())
+        .del::<A>()
+        .replace_named("slot", A);
+
+    let mut world = World::new();
+    let entity = world.spawn_dyn(dyn_bundle);
+    assert!(!entity.contains::<A>());
+}
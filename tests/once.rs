@@ -0,0 +1,82 @@
+use bevy_ecs::{event::Events, prelude::*};
+use dynamic_bundling::{
+    DynBundle, DynBundleError, DynBundleErrorPolicy, DynBundleFailed, DynBundleWorldExt,
+};
+
+/// A component that can't be cloned, so it can only be inserted with `add_once`.
+#[derive(Component, Debug, PartialEq)]
+struct Unique(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Plain;
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(DynBundleErrorPolicy::Event);
+    world.init_resource::<Events<DynBundleFailed>>();
+    world
+}
+
+fn failures(world: &mut World) -> Vec<(Entity, DynBundleError)> {
+    world
+        .resource_mut::<Events<DynBundleFailed>>()
+        .drain()
+        .map(|failed| (failed.entity, failed.reason))
+        .collect()
+}
+
+#[test]
+fn applied_once() {
+    let mut world = world();
+    let entity = world.spawn_dyn(DynBundle::new().add_once(Unique(1))).id();
+    assert_eq!(world.get::<Unique>(entity), Some(&Unique(1)));
+    assert!(failures(&mut world).is_empty());
+}
+
+#[test]
+fn applied_twice_is_reported() {
+    let mut world = world();
+    let dyn_bundle = DynBundle::new().add_once(Unique(1)).add(Plain);
+    let first = world.spawn_dyn(dyn_bundle.clone()).id();
+    let second = world.spawn_dyn(dyn_bundle).id();
+
+    assert_eq!(world.get::<Unique>(first), Some(&Unique(1)));
+    assert!(!world.entity(second).contains::<Unique>());
+    // The rest of the bundle is still applied.
+    assert!(world.entity(second).contains::<Plain>());
+    assert_eq!(
+        failures(&mut world),
+        [(second, DynBundleError::AlreadyApplied)]
+    );
+}
+
+#[test]
+fn batch_spawn_reports_every_extra_entity() {
+    let mut world = world();
+    let entities = world.spawn_dyn_batch(DynBundle::new().add_once(Unique(1)), 3);
+
+    assert_eq!(world.get::<Unique>(entities[0]), Some(&Unique(1)));
+    assert_eq!(
+        failures(&mut world),
+        [
+            (entities[1], DynBundleError::AlreadyApplied),
+            (entities[2], DynBundleError::AlreadyApplied),
+        ]
+    );
+}
+
+#[test]
+fn component_applied_twice_is_reported() {
+    let mut world = world();
+    let dyn_bundle = DynBundle::new().add_once(Unique(1));
+    let first = world.spawn(dyn_bundle.clone()).id();
+    let second = world.spawn(dyn_bundle).id();
+    world.flush();
+
+    assert_eq!(world.get::<Unique>(first), Some(&Unique(1)));
+    assert!(!world.entity(second).contains::<Unique>());
+    assert_eq!(
+        failures(&mut world),
+        [(second, DynBundleError::AlreadyApplied)]
+    );
+}
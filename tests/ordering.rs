@@ -0,0 +1,97 @@
+use bevy_ecs::prelude::*;
+use dynamic_bundling::{DynBundle, DynBundleWorldExt};
+use proptest::{collection::vec, prelude::*};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct X(u8);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Y(u8);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Z(u8);
+
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    Add(usize, u8),
+    Del(usize),
+}
+
+type State = [Option<u8>; 3];
+
+fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        (0..3usize, any::<u8>()).prop_map(|(component, value)| Step::Add(component, value)),
+        (0..3usize).prop_map(Step::Del),
+    ]
+}
+
+fn build(steps: &[Step]) -> DynBundle {
+    steps
+        .iter()
+        .fold(DynBundle::new(), |acc, step| match *step {
+            Step::Add(0, value) => acc.add(X(value)),
+            Step::Add(1, value) => acc.add(Y(value)),
+            Step::Add(_, value) => acc.add(Z(value)),
+            Step::Del(0) => acc.del::<X>(),
+            Step::Del(1) => acc.del::<Y>(),
+            Step::Del(_) => acc.del::<Z>(),
+        })
+}
+
+/// The expected state after applying `steps` one after the other, the last write winning.
+fn model<'a>(steps: impl IntoIterator<Item = &'a Step>) -> State {
+    let mut state = State::default();
+    for step in steps {
+        match *step {
+            Step::Add(component, value) => state[component] = Some(value),
+            Step::Del(component) => state[component] = None,
+        }
+    }
+    state
+}
+
+fn observe(dyn_bundle: DynBundle) -> State {
+    let mut world = World::new();
+    let entity = world.spawn_dyn(dyn_bundle);
+    [
+        entity.get::<X>().map(|x| x.0),
+        entity.get::<Y>().map(|y| y.0),
+        entity.get::<Z>().map(|z| z.0),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn ops_apply_left_to_right(steps in vec(step(), 0..32)) {
+        prop_assert_eq!(observe(build(&steps)), model(&steps));
+    }
+
+    #[test]
+    fn append_runs_after(first in vec(step(), 0..16), second in vec(step(), 0..16)) {
+        prop_assert_eq!(
+            observe(build(&first).append(build(&second))),
+            model(first.iter().chain(&second))
+        );
+    }
+
+    #[test]
+    fn prepend_runs_before(first in vec(step(), 0..16), second in vec(step(), 0..16)) {
+        prop_assert_eq!(
+            observe(build(&first).prepend(build(&second))),
+            model(second.iter().chain(&first))
+        );
+    }
+
+    #[test]
+    fn append_is_associative(
+        first in vec(step(), 0..8),
+        second in vec(step(), 0..8),
+        third in vec(step(), 0..8),
+    ) {
+        prop_assert_eq!(
+            observe(build(&first).append(build(&second)).append(build(&third))),
+            observe(build(&first).append(build(&second).append(build(&third))))
+        );
+    }
+}
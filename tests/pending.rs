@@ -0,0 +1,118 @@
+use std::{
+    fmt::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use bevy_ecs::prelude::*;
+use bevy_utils::tracing::{
+    field::{Field, Visit},
+    span, subscriber, Event, Level, Metadata, Subscriber,
+};
+use dynamic_bundling::{warn_stale_pending_dyn_bundles, DynBundle, PendingDynBundle};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct A;
+
+/// Collects the messages of warnings logged while it is the default subscriber.
+#[derive(Clone, Default)]
+struct Warnings(Arc<Mutex<Vec<String>>>);
+
+impl Warnings {
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+impl Subscriber for Warnings {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() == Level::WARN
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut message = String::new();
+        event.record(&mut Message(&mut message));
+        self.0.lock().unwrap().push(message);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+struct Message<'a>(&'a mut String);
+
+impl Visit for Message<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            write!(self.0, "{value:?}").unwrap();
+        }
+    }
+}
+
+#[test]
+fn warns_about_entities_pending_across_runs() {
+    let warnings = Warnings::default();
+    subscriber::with_default(warnings.clone(), || {
+        let mut world = World::new();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(warn_stale_pending_dyn_bundles);
+
+        // Inserted directly into the world, so the command queued by the hook waits for the
+        // next flush.
+        let entity = world.spawn(DynBundle::new().add(A)).id();
+        assert_eq!(
+            world
+                .query_filtered::<Entity, PendingDynBundle>()
+                .iter(&world)
+                .collect::<Vec<_>>(),
+            [entity]
+        );
+
+        // The first run only records the entity.
+        schedule.run(&mut world);
+        assert!(warnings.take().is_empty());
+
+        schedule.run(&mut world);
+        let warned = warnings.take();
+        assert_eq!(warned.len(), 1, "{warned:?}");
+        assert!(warned[0].contains(&format!("{entity:?}")), "{warned:?}");
+
+        // Still pending, so it is reported on every run.
+        schedule.run(&mut world);
+        assert_eq!(warnings.take().len(), 1);
+
+        world.flush();
+        assert!(world.entity(entity).contains::<A>());
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+        assert!(warnings.take().is_empty());
+    });
+}
+
+#[test]
+fn entities_applied_between_runs_are_not_reported() {
+    let warnings = Warnings::default();
+    subscriber::with_default(warnings.clone(), || {
+        let mut world = World::new();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(warn_stale_pending_dyn_bundles);
+
+        let first = world.spawn(DynBundle::new().add(A)).id();
+        schedule.run(&mut world);
+        world.flush();
+        assert!(world.entity(first).contains::<A>());
+
+        // A different entity pending on the next run hasn't been pending for two runs yet.
+        world.spawn(DynBundle::new().add(A));
+        schedule.run(&mut world);
+        assert!(warnings.take().is_empty());
+    });
+}
@@ -0,0 +1,138 @@
+use bevy_ecs::{
+    event::Events,
+    prelude::*,
+    reflect::{AppTypeRegistry, ReflectComponent},
+};
+use bevy_reflect::{DynamicStruct, Reflect, TypePath};
+use dynamic_bundling::{
+    DynBundle, DynBundleError, DynBundleErrorPolicy, DynBundleFailed, DynBundleWorldExt,
+};
+
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component)]
+struct Registered(u32);
+
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+struct Unregistered(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Plain;
+
+fn world() -> World {
+    let mut world = World::new();
+    world.init_resource::<AppTypeRegistry>();
+    world
+        .resource::<AppTypeRegistry>()
+        .write()
+        .register::<Registered>();
+    world.insert_resource(DynBundleErrorPolicy::Event);
+    world.init_resource::<Events<DynBundleFailed>>();
+    world
+}
+
+fn failures(world: &mut World) -> Vec<DynBundleError> {
+    world
+        .resource_mut::<Events<DynBundleFailed>>()
+        .drain()
+        .map(|failed| failed.reason)
+        .collect()
+}
+
+#[test]
+fn registered_components() {
+    let mut world = world();
+    let entity = world
+        .spawn_dyn(
+            DynBundle::new()
+                .add_reflect(Box::new(Registered(1)))
+                .add(Plain)
+                .del_by_type_path(Registered::type_path()),
+        )
+        .id();
+    assert!(!world.entity(entity).contains::<Registered>());
+    assert!(world.entity(entity).contains::<Plain>());
+
+    world
+        .entity_mut(entity)
+        .insert(DynBundle::new().add_reflect(Box::new(Registered(2))));
+    world.flush();
+    assert_eq!(world.get::<Registered>(entity), Some(&Registered(2)));
+    assert!(failures(&mut world).is_empty());
+}
+
+#[test]
+fn unregistered_type_is_reported() {
+    let mut world = world();
+    let entity = world
+        .spawn_dyn(
+            DynBundle::new()
+                .add_reflect(Box::new(Unregistered(1)))
+                .del_by_type_path("no::such::Type")
+                .add(Plain),
+        )
+        .id();
+    assert!(!world.entity(entity).contains::<Unregistered>());
+    assert!(world.entity(entity).contains::<Plain>());
+    assert_eq!(
+        failures(&mut world),
+        [
+            DynBundleError::UnregisteredComponent(Unregistered::type_path().into()),
+            DynBundleError::UnregisteredComponent("no::such::Type".into()),
+        ]
+    );
+}
+
+#[test]
+fn missing_registry_is_reported() {
+    let mut world = World::new();
+    world.insert_resource(DynBundleErrorPolicy::Event);
+    world.init_resource::<Events<DynBundleFailed>>();
+    let entity = world
+        .spawn_dyn(
+            DynBundle::new()
+                .add_reflect(Box::new(Registered(1)))
+                .del_by_type_path(Registered::type_path())
+                .add(Plain),
+        )
+        .id();
+    assert!(world.entity(entity).contains::<Plain>());
+    assert_eq!(
+        failures(&mut world),
+        [
+            DynBundleError::MissingTypeRegistry,
+            DynBundleError::MissingTypeRegistry,
+        ]
+    );
+}
+
+#[test]
+fn dynamic_value_without_type_is_reported() {
+    let mut world = world();
+    let mut dynamic = DynamicStruct::default();
+    dynamic.insert("value", 1u32);
+    let type_path = DynamicStruct::type_path();
+
+    // Building the bundle doesn't panic, the failure is reported when it's applied.
+    let dyn_bundle = DynBundle::new().add_reflect(Box::new(dynamic)).add(Plain);
+    let entity = world.spawn_dyn(dyn_bundle).id();
+    assert!(world.entity(entity).contains::<Plain>());
+    assert_eq!(
+        failures(&mut world),
+        [DynBundleError::MissingTypeInfo(type_path.into())]
+    );
+}
+
+#[test]
+fn ignore_policy_skips_failures() {
+    let mut world = world();
+    world.insert_resource(DynBundleErrorPolicy::Ignore);
+    let entity = world
+        .spawn_dyn(
+            DynBundle::new()
+                .del_by_type_path("no::such::Type")
+                .add(Plain),
+        )
+        .id();
+    assert!(world.entity(entity).contains::<Plain>());
+    assert!(failures(&mut world).is_empty());
+}
@@ -0,0 +1,205 @@
+use bevy_ecs::{event::Events, prelude::*};
+use dynamic_bundling::{
+    DynBundle, DynBundleApplied, DynBundleError, DynBundleErrorPolicy, DynBundleFailed,
+    DynBundleReinsertPolicy, OnDynBundleApplied, PendingDynBundle,
+};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct A(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct B;
+
+#[derive(Resource, Default)]
+struct Applied(u32);
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(DynBundleErrorPolicy::Event);
+    world.init_resource::<Events<DynBundleFailed>>();
+    world.init_resource::<Applied>();
+    world.add_observer(
+        |_: Trigger<OnDynBundleApplied>, mut applied: ResMut<Applied>| {
+            applied.0 += 1;
+        },
+    );
+    world
+}
+
+fn failures(world: &mut World) -> Vec<DynBundleError> {
+    world
+        .resource_mut::<Events<DynBundleFailed>>()
+        .drain()
+        .map(|failed| failed.reason)
+        .collect()
+}
+
+fn pending(world: &mut World) -> usize {
+    world
+        .query_filtered::<Entity, PendingDynBundle>()
+        .iter(world)
+        .count()
+}
+
+#[test]
+fn inserted_twice_in_one_command_buffer() {
+    let mut world = world();
+    let entity = world.spawn_empty().id();
+    world
+        .commands()
+        .entity(entity)
+        .insert(DynBundle::new().add(A(1)).add(B))
+        .insert(DynBundle::new().add(A(2)));
+    world.flush();
+
+    assert_eq!(world.get::<A>(entity), Some(&A(2)));
+    assert!(world.entity(entity).contains::<B>());
+    assert!(!world.entity(entity).contains::<DynBundle>());
+    assert_eq!(pending(&mut world), 0);
+    assert_eq!(world.resource::<Applied>().0, 1);
+    assert!(failures(&mut world).is_empty());
+}
+
+#[test]
+fn inserted_three_times_applies_in_order() {
+    let mut world = world();
+    let entity = world.spawn(DynBundle::new().add(A(1))).id();
+    world
+        .entity_mut(entity)
+        .insert(DynBundle::new().with(|entity| entity.get_mut::<A>().unwrap().0 *= 10));
+    world
+        .entity_mut(entity)
+        .insert(DynBundle::new().with(|entity| entity.get_mut::<A>().unwrap().0 += 3));
+    world.flush();
+
+    assert_eq!(world.get::<A>(entity), Some(&A(13)));
+    assert_eq!(world.resource::<Applied>().0, 1);
+    assert!(failures(&mut world).is_empty());
+}
+
+#[test]
+fn on_applied_of_a_replaced_bundle_runs_last() {
+    let mut world = world();
+    let entity = world.spawn_empty().id();
+    world
+        .commands()
+        .entity(entity)
+        .insert(
+            DynBundle::new()
+                .on_applied(|entity| {
+                    let b = entity.contains::<B>();
+                    entity.insert(A(u32::from(b)));
+                })
+                .mark_applied(),
+        )
+        .insert(DynBundle::new().add(B));
+    world.flush();
+
+    assert_eq!(world.get::<A>(entity), Some(&A(1)));
+    assert!(world.entity(entity).contains::<DynBundleApplied>());
+    assert_eq!(world.resource::<Applied>().0, 1);
+}
+
+#[test]
+fn replace_policy_applies_the_last_bundle() {
+    let mut world = world();
+    world.insert_resource(DynBundleReinsertPolicy::Replace);
+    let entity = world.spawn_empty().id();
+    world
+        .commands()
+        .entity(entity)
+        .insert(DynBundle::new().add(A(1)).add(B))
+        .insert(DynBundle::new().add(A(2)));
+    world.flush();
+
+    assert_eq!(world.get::<A>(entity), Some(&A(2)));
+    assert!(!world.entity(entity).contains::<B>());
+    assert_eq!(world.resource::<Applied>().0, 1);
+    assert!(failures(&mut world).is_empty());
+}
+
+#[test]
+fn earlier_bundle_removing_the_component() {
+    let mut world = world();
+    let entity = world.spawn_empty().id();
+    world
+        .commands()
+        .entity(entity)
+        .insert(DynBundle::new().add(A(1)).del::<DynBundle>())
+        .insert(DynBundle::new().add(B));
+    world.flush();
+
+    assert_eq!(world.get::<A>(entity), Some(&A(1)));
+    assert!(world.entity(entity).contains::<B>());
+    assert!(failures(&mut world).is_empty());
+}
+
+#[test]
+fn earlier_bundle_retaining_other_components() {
+    let mut world = world();
+    let entity = world.spawn_empty().id();
+    world
+        .commands()
+        .entity(entity)
+        .insert(DynBundle::new().add(A(1)).del_all_except::<A>())
+        .insert(DynBundle::new().add(B));
+    world.flush();
+
+    assert_eq!(world.get::<A>(entity), Some(&A(1)));
+    assert!(world.entity(entity).contains::<B>());
+    assert!(failures(&mut world).is_empty());
+}
+
+#[test]
+fn removed_before_applied() {
+    let mut world = world();
+    let entity = world.spawn_empty().id();
+    world
+        .commands()
+        .entity(entity)
+        .insert(DynBundle::new().add(A(1)))
+        .insert(DynBundle::new().add(B))
+        .remove::<DynBundle>();
+    world.flush();
+
+    assert!(!world.entity(entity).contains::<A>());
+    assert!(!world.entity(entity).contains::<B>());
+    assert_eq!(pending(&mut world), 0);
+    assert_eq!(world.resource::<Applied>().0, 0);
+    assert_eq!(failures(&mut world), [DynBundleError::ComponentNotFound]);
+}
+
+#[test]
+fn reinserted_after_removal() {
+    let mut world = world();
+    let entity = world.spawn_empty().id();
+    world
+        .commands()
+        .entity(entity)
+        .insert(DynBundle::new().add(A(1)))
+        .remove::<DynBundle>()
+        .insert(DynBundle::new().add(B));
+    world.flush();
+
+    assert!(!world.entity(entity).contains::<A>());
+    assert!(world.entity(entity).contains::<B>());
+    assert_eq!(world.resource::<Applied>().0, 1);
+    assert!(failures(&mut world).is_empty());
+}
+
+#[test]
+fn inserted_again_by_its_own_bundle() {
+    let mut world = world();
+    let entity = world
+        .spawn(DynBundle::new().add(A(1)).with(|entity| {
+            entity.insert(DynBundle::new().add(B));
+        }))
+        .id();
+    world.flush();
+
+    assert_eq!(world.get::<A>(entity), Some(&A(1)));
+    assert!(world.entity(entity).contains::<B>());
+    assert_eq!(pending(&mut world), 0);
+    assert_eq!(world.resource::<Applied>().0, 2);
+    assert!(failures(&mut world).is_empty());
+}
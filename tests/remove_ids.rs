@@ -0,0 +1,91 @@
+use bevy_ecs::{component::ComponentId, prelude::*};
+use dynamic_bundling::DynBundle;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct A;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct B;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct C;
+
+fn components(world: &World, entity: Entity) -> (bool, bool, bool) {
+    let entity = world.entity(entity);
+    (
+        entity.contains::<A>(),
+        entity.contains::<B>(),
+        entity.contains::<C>(),
+    )
+}
+
+#[test]
+fn del_id() {
+    let mut world = World::new();
+    let entity = world.spawn((A, B)).id();
+    let a = world.component_id::<A>().unwrap();
+    DynBundle::new()
+        .del_id(a)
+        .apply_to(&mut world.entity_mut(entity));
+    assert_eq!(components(&world, entity), (false, true, false));
+}
+
+#[test]
+fn del_ids() {
+    let mut world = World::new();
+    let entity = world.spawn((A, B, C)).id();
+    let ids = [
+        world.component_id::<A>().unwrap(),
+        world.component_id::<C>().unwrap(),
+    ];
+    DynBundle::new()
+        .del_ids(&ids)
+        .apply_to(&mut world.entity_mut(entity));
+    assert_eq!(components(&world, entity), (false, true, false));
+}
+
+#[test]
+fn del_ids_after_add() {
+    let mut world = World::new();
+    let c = world.register_component::<C>();
+    let entity = world.spawn(A).id();
+    DynBundle::new()
+        .add(C)
+        .del_id(c)
+        .apply_to(&mut world.entity_mut(entity));
+    assert_eq!(components(&world, entity), (true, false, false));
+}
+
+#[test]
+fn del_ids_skips_absent_components() {
+    let mut world = World::new();
+    let b = world.register_component::<B>();
+    let entity = world.spawn(A).id();
+    DynBundle::new()
+        .del_id(b)
+        .apply_to(&mut world.entity_mut(entity));
+    assert_eq!(components(&world, entity), (true, false, false));
+}
+
+#[test]
+fn del_ids_skips_unregistered_ids() {
+    let mut world = World::new();
+    let entity = world.spawn(A).id();
+    let unregistered = ComponentId::new(10_000);
+    assert!(world.components().get_info(unregistered).is_none());
+    DynBundle::new()
+        .del_id(unregistered)
+        .add(B)
+        .apply_to(&mut world.entity_mut(entity));
+    assert_eq!(components(&world, entity), (true, true, false));
+}
+
+#[test]
+fn del_all_except() {
+    let mut world = World::new();
+    let entity = world.spawn((A, B, C)).id();
+    DynBundle::new()
+        .del_all_except::<(A, C)>()
+        .apply_to(&mut world.entity_mut(entity));
+    assert_eq!(components(&world, entity), (true, false, true));
+}
@@ -0,0 +1,148 @@
+#![cfg(feature = "serialize")]
+
+use bevy_ecs::{
+    prelude::*,
+    reflect::{AppTypeRegistry, ReflectComponent},
+};
+use bevy_reflect::{Reflect, TypePath, TypeRegistry};
+use dynamic_bundling::{
+    serde::{DynBundleDeserializer, DynBundleSerializer},
+    DynBundle, DynBundleWorldExt,
+};
+use serde::de::DeserializeSeed;
+
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component)]
+struct Health(u32);
+
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component)]
+struct Label {
+    text: String,
+    tags: Vec<String>,
+}
+
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component)]
+struct Armor;
+
+#[derive(Reflect, Clone, Debug, PartialEq)]
+struct NotAComponent;
+
+fn world() -> World {
+    let mut world = World::new();
+    world.init_resource::<AppTypeRegistry>();
+    {
+        let mut registry = world.resource::<AppTypeRegistry>().write();
+        registry.register::<Health>();
+        registry.register::<Label>();
+        registry.register::<Armor>();
+        registry.register::<NotAComponent>();
+    }
+    world
+}
+
+fn serialize(dyn_bundle: &DynBundle, registry: &TypeRegistry) -> String {
+    ron::to_string(&DynBundleSerializer::new(dyn_bundle, registry)).unwrap()
+}
+
+fn deserialize(text: &str, registry: &TypeRegistry) -> Result<DynBundle, ron::Error> {
+    let mut deserializer = ron::de::Deserializer::from_str(text).unwrap();
+    DynBundleDeserializer::new(registry).deserialize(&mut deserializer)
+}
+
+fn sample() -> DynBundle {
+    DynBundle::new()
+        .add_reflect(Box::new(Armor))
+        .add_reflect(Box::new(Health(10)))
+        .add_reflect(Box::new(Label {
+            text: "door".to_owned(),
+            tags: vec!["wooden".to_owned(), "locked".to_owned()],
+        }))
+        .del_by_type_path(Armor::type_path())
+}
+
+#[test]
+fn round_trip_preserves_ops() {
+    let mut world = world();
+    let loaded = {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let text = serialize(&sample(), &registry);
+        let loaded = deserialize(&text, &registry).unwrap();
+        assert_eq!(loaded.len(), sample().len());
+        assert_eq!(serialize(&loaded, &registry), text);
+        loaded
+    };
+
+    let original = world.spawn_dyn(sample()).id();
+    let loaded = world.spawn_dyn(loaded).id();
+    for entity in [original, loaded] {
+        let entity = world.entity(entity);
+        assert_eq!(entity.get::<Health>(), Some(&Health(10)));
+        assert_eq!(entity.get::<Label>().map(|label| label.tags.len()), Some(2));
+        assert!(!entity.contains::<Armor>());
+    }
+}
+
+#[test]
+fn unknown_remove_path_fails_to_load() {
+    let world = world();
+    let registry = world.resource::<AppTypeRegistry>().read();
+
+    let text = r#"[(op: remove, type_path: "no::such::Type")]"#;
+    let error = deserialize(text, &registry).unwrap_err();
+    assert!(error.to_string().contains("no::such::Type"), "{error}");
+}
+
+#[test]
+fn non_component_paths_fail_to_load() {
+    let world = world();
+    let registry = world.resource::<AppTypeRegistry>().read();
+
+    let remove = format!(
+        r#"[(op: remove, type_path: "{}")]"#,
+        NotAComponent::type_path()
+    );
+    assert!(deserialize(&remove, &registry).is_err());
+
+    let insert = format!(
+        r#"[(op: insert, type_path: "{}", value: ())]"#,
+        NotAComponent::type_path()
+    );
+    assert!(deserialize(&insert, &registry).is_err());
+}
+
+#[test]
+fn non_reflect_ops_fail_to_serialize() {
+    let world = world();
+    let registry = world.resource::<AppTypeRegistry>().read();
+
+    let dyn_bundle = DynBundle::new().add(Health(1));
+    assert!(ron::to_string(&DynBundleSerializer::new(&dyn_bundle, &registry)).is_err());
+}
+
+#[test]
+fn round_trip_preserves_keep_existing() {
+    let mut world = world();
+    let dyn_bundle = DynBundle::new()
+        .add_reflect(Box::new(Health(10)))
+        .keep_existing()
+        .add_reflect(Box::new(Armor));
+    let loaded = {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let text = serialize(&dyn_bundle, &registry);
+        assert!(text.contains("insert_if_new"), "{text}");
+        let loaded = deserialize(&text, &registry).unwrap();
+        assert_eq!(serialize(&loaded, &registry), text);
+        loaded
+    };
+
+    let original = world.spawn(Health(1)).id();
+    dyn_bundle.apply_to(&mut world.entity_mut(original));
+    let entity = world.spawn(Health(1)).id();
+    loaded.apply_to(&mut world.entity_mut(entity));
+    for entity in [original, entity] {
+        assert_eq!(world.get::<Health>(entity), Some(&Health(1)));
+        assert!(world.entity(entity).contains::<Armor>());
+    }
+}
@@ -0,0 +1,154 @@
+use bevy_ecs::{event::Events, prelude::*};
+use dynamic_bundling::{
+    apply_dyn_bundle_sources, AppliedDynBundleSource, DynBundle, DynBundleErrorPolicy,
+    DynBundleFailed, DynBundleSource,
+};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct A(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct B;
+
+/// Owned by `add_once`, so it doesn't need to be `Clone`.
+#[derive(Component, Debug, PartialEq)]
+struct Once(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Child;
+
+struct Sources {
+    world: World,
+    schedule: Schedule,
+}
+
+impl Sources {
+    fn new() -> Self {
+        let mut world = World::new();
+        world.insert_resource(DynBundleErrorPolicy::Event);
+        world.init_resource::<Events<DynBundleFailed>>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(apply_dyn_bundle_sources);
+        Sources { world, schedule }
+    }
+
+    fn spawn(&mut self, dyn_bundle: DynBundle) -> Entity {
+        let entity = self.world.spawn(DynBundleSource(dyn_bundle)).id();
+        self.update();
+        entity
+    }
+
+    fn set(&mut self, entity: Entity, dyn_bundle: DynBundle) {
+        self.world.get_mut::<DynBundleSource>(entity).unwrap().0 = dyn_bundle;
+        self.update();
+    }
+
+    fn update(&mut self) {
+        self.schedule.run(&mut self.world);
+        self.world.flush();
+        let failures = self
+            .world
+            .resource_mut::<Events<DynBundleFailed>>()
+            .drain()
+            .count();
+        assert_eq!(failures, 0, "applying a source reported an error");
+    }
+
+    fn children(&mut self) -> usize {
+        self.world
+            .query_filtered::<(), With<Child>>()
+            .iter(&self.world)
+            .count()
+    }
+}
+
+#[test]
+fn components_of_the_previous_version_are_removed() {
+    let mut sources = Sources::new();
+    let entity = sources.spawn(DynBundle::new().add(A(1)).add(B));
+    assert_eq!(sources.world.get::<A>(entity), Some(&A(1)));
+    assert!(sources.world.entity(entity).contains::<B>());
+
+    sources.set(entity, DynBundle::new().add(A(2)));
+    assert_eq!(sources.world.get::<A>(entity), Some(&A(2)));
+    assert!(!sources.world.entity(entity).contains::<B>());
+    assert!(sources
+        .world
+        .entity(entity)
+        .contains::<AppliedDynBundleSource>());
+}
+
+#[test]
+fn children_are_spawned_once() {
+    let mut sources = Sources::new();
+    let first = DynBundle::new().add(A(1)).with_child(Child);
+    let entity = sources.spawn(first.clone());
+    assert_eq!(sources.children(), 1);
+
+    // Later versions share the child operation of the first one.
+    let second = first.add(A(2));
+    sources.set(entity, second.clone());
+    sources.set(entity, second.add(A(3)));
+    assert_eq!(sources.world.get::<A>(entity), Some(&A(3)));
+    assert_eq!(sources.children(), 1);
+}
+
+#[test]
+fn add_once_is_applied_once() {
+    let mut sources = Sources::new();
+    let first = DynBundle::new().add_once(Once(1));
+    let entity = sources.spawn(first.clone());
+    assert_eq!(sources.world.get::<Once>(entity), Some(&Once(1)));
+
+    // The new version shares the `add_once` value, which the first application took.
+    sources.set(entity, first.add(A(2)));
+    assert_eq!(sources.world.get::<Once>(entity), Some(&Once(1)));
+    assert_eq!(sources.world.get::<A>(entity), Some(&A(2)));
+}
+
+#[test]
+fn once_only_operations_of_a_later_version_are_applied() {
+    let mut sources = Sources::new();
+    let first = DynBundle::new().add(A(1));
+    let entity = sources.spawn(first.clone());
+
+    let second = first.add_once(Once(2)).with_child(Child);
+    sources.set(entity, second.clone());
+    assert_eq!(sources.world.get::<Once>(entity), Some(&Once(2)));
+    assert_eq!(sources.children(), 1);
+
+    sources.set(entity, second.add(B));
+    assert_eq!(sources.world.get::<Once>(entity), Some(&Once(2)));
+    assert!(sources.world.entity(entity).contains::<B>());
+    assert_eq!(sources.children(), 1);
+}
+
+#[test]
+fn closures_run_on_every_change() {
+    let mut sources = Sources::new();
+    let bump = |entity: &mut EntityWorldMut| {
+        let count = entity.get::<A>().map_or(0, |a| a.0);
+        entity.insert(A(count + 1));
+    };
+    let entity = sources.spawn(DynBundle::new().with(bump));
+    sources.set(entity, DynBundle::new().with(bump).add(B));
+    assert_eq!(sources.world.get::<A>(entity), Some(&A(2)));
+    assert!(sources.world.entity(entity).contains::<B>());
+}
+
+#[test]
+fn modify_runs_on_every_change() {
+    let mut sources = Sources::new();
+    let first = DynBundle::new().modify::<A>(|a| a.0 += 10);
+    // `A` comes from outside the source, so the bundle only modifies it.
+    let entity = sources
+        .world
+        .spawn((A(1), DynBundleSource(first.clone())))
+        .id();
+    sources.update();
+    assert_eq!(sources.world.get::<A>(entity), Some(&A(11)));
+
+    sources.set(entity, first.add(B));
+    assert_eq!(sources.world.get::<A>(entity), Some(&A(21)));
+    assert!(sources.world.entity(entity).contains::<B>());
+}
@@ -0,0 +1,42 @@
+use bevy_ecs::prelude::*;
+use dynamic_bundling::{DynBundle, DynBundleWorldExt};
+
+const OPS: u32 = 100_000;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Counter(u32);
+
+#[test]
+fn many_ops() {
+    let dyn_bundle = (0..OPS).fold(DynBundle::new(), |acc, i| acc.add(Counter(i)));
+    assert_eq!(dyn_bundle.len(), OPS as usize);
+
+    let mut world = World::new();
+    let entity = world.spawn_dyn(dyn_bundle);
+    assert_eq!(entity.get::<Counter>(), Some(&Counter(OPS - 1)));
+}
+
+#[test]
+fn many_appends() {
+    let dyn_bundle = (0..OPS).fold(DynBundle::new(), |acc, i| {
+        acc.append(DynBundle::new_add(Counter(i)).del::<Counter>())
+    });
+    assert_eq!(dyn_bundle.len(), 2 * OPS as usize);
+
+    let mut world = World::new();
+    let entity = world.spawn_dyn(dyn_bundle.add(Counter(OPS)));
+    assert_eq!(entity.get::<Counter>(), Some(&Counter(OPS)));
+}
+
+#[test]
+fn many_children() {
+    let dyn_bundle = DynBundle::new().with_children((0..OPS / 10).map(Counter));
+
+    let mut world = World::new();
+    let entity = world.spawn_dyn(dyn_bundle).id();
+    assert_eq!(
+        world.query::<&Counter>().iter(&world).count(),
+        (OPS / 10) as usize
+    );
+    assert!(!world.entity(entity).contains::<Counter>());
+}
@@ -0,0 +1,229 @@
+use bevy_ecs::{
+    prelude::*,
+    reflect::{AppTypeRegistry, ReflectComponent},
+};
+use bevy_hierarchy::{BuildChildren, Children, Parent};
+use bevy_reflect::Reflect;
+use dynamic_bundling::{DynBundle, DynBundleError, UndoStack};
+
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component)]
+struct A(u32);
+
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component)]
+struct B(u32);
+
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component)]
+struct C(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Leaf(u32);
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Marker;
+
+fn world() -> World {
+    let mut world = World::new();
+    world.init_resource::<AppTypeRegistry>();
+    {
+        let mut registry = world.resource::<AppTypeRegistry>().write();
+        registry.register::<A>();
+        registry.register::<B>();
+        registry.register::<C>();
+    }
+    world
+}
+
+fn state(world: &World, entity: Entity) -> (Option<A>, Option<B>, Option<C>) {
+    (
+        world.get::<A>(entity).cloned(),
+        world.get::<B>(entity).cloned(),
+        world.get::<C>(entity).cloned(),
+    )
+}
+
+/// Returns the `Leaf` values of all children of `entity`, and checks that each child points
+/// back to it.
+fn leaves(world: &World, entity: Entity) -> Vec<u32> {
+    let Some(children) = world.get::<Children>(entity) else {
+        return Vec::new();
+    };
+    children
+        .iter()
+        .map(|&child| {
+            assert_eq!(world.get::<Parent>(child).map(Parent::get), Some(entity));
+            world.get::<Leaf>(child).map_or(0, |leaf| leaf.0)
+        })
+        .collect()
+}
+
+fn leaf_count(world: &mut World) -> usize {
+    world.query::<&Leaf>().iter(world).count()
+}
+
+#[test]
+fn apply_undo_redo_restores_values() {
+    let mut world = world();
+    let entity = world.spawn((A(1), C(3))).id();
+    let mut stack = UndoStack::default();
+
+    stack
+        .apply(
+            &mut world,
+            entity,
+            DynBundle::new().add(A(2)).add(B(5)).del::<C>(),
+        )
+        .unwrap();
+    let applied = (Some(A(2)), Some(B(5)), None);
+    let original = (Some(A(1)), None, Some(C(3)));
+    assert_eq!(state(&world, entity), applied);
+
+    assert!(stack.undo(&mut world).unwrap());
+    assert_eq!(state(&world, entity), original);
+
+    assert!(stack.redo(&mut world).unwrap());
+    assert_eq!(state(&world, entity), applied);
+
+    assert!(stack.undo(&mut world).unwrap());
+    assert_eq!(state(&world, entity), original);
+    assert!(!stack.undo(&mut world).unwrap());
+}
+
+#[test]
+fn apply_undo_redo_with_children() {
+    let mut world = world();
+    let entity = world.spawn(A(1)).id();
+    let mut stack = UndoStack::default();
+
+    stack
+        .apply(
+            &mut world,
+            entity,
+            DynBundle::new().add(A(2)).with_child(Leaf(1)),
+        )
+        .unwrap();
+    assert_eq!(leaves(&world, entity), [1]);
+    assert_eq!(world.get::<A>(entity), Some(&A(2)));
+
+    assert!(stack.undo(&mut world).unwrap());
+    assert!(!world.entity(entity).contains::<Children>());
+    assert_eq!(leaf_count(&mut world), 0);
+    assert_eq!(world.get::<A>(entity), Some(&A(1)));
+
+    assert!(stack.redo(&mut world).unwrap());
+    assert_eq!(leaves(&world, entity), [1]);
+    assert_eq!(leaf_count(&mut world), 1);
+    assert_eq!(world.get::<A>(entity), Some(&A(2)));
+
+    assert!(stack.undo(&mut world).unwrap());
+    assert!(!world.entity(entity).contains::<Children>());
+    assert_eq!(leaf_count(&mut world), 0);
+}
+
+#[test]
+fn undo_despawns_grandchildren() {
+    let mut world = world();
+    let entity = world.spawn_empty().id();
+    let mut stack = UndoStack::default();
+
+    stack
+        .apply(
+            &mut world,
+            entity,
+            DynBundle::new().with_child(DynBundle::new().add(Leaf(1)).with_child(Leaf(2))),
+        )
+        .unwrap();
+    assert_eq!(leaf_count(&mut world), 2);
+
+    assert!(stack.undo(&mut world).unwrap());
+    assert_eq!(leaf_count(&mut world), 0);
+
+    assert!(stack.redo(&mut world).unwrap());
+    assert_eq!(leaves(&world, entity), [1]);
+    assert_eq!(leaf_count(&mut world), 2);
+}
+
+#[test]
+fn undo_keeps_existing_children() {
+    let mut world = world();
+    let entity = world.spawn_empty().id();
+    let existing = world.spawn(Leaf(0)).id();
+    world.entity_mut(entity).add_child(existing);
+    let mut stack = UndoStack::default();
+
+    stack
+        .apply(&mut world, entity, DynBundle::new().with_child(Leaf(1)))
+        .unwrap();
+    assert_eq!(leaves(&world, entity), [0, 1]);
+
+    assert!(stack.undo(&mut world).unwrap());
+    assert_eq!(leaves(&world, entity), [0]);
+
+    assert!(stack.redo(&mut world).unwrap());
+    assert_eq!(leaves(&world, entity), [0, 1]);
+}
+
+#[test]
+fn failed_undo_and_redo_keep_the_history() {
+    let mut world = world();
+    let first = world.spawn(A(1)).id();
+    let second = world.spawn(A(1)).id();
+    let mut stack = UndoStack::default();
+    stack
+        .apply(&mut world, first, DynBundle::new().add(A(2)))
+        .unwrap();
+    stack
+        .apply(&mut world, second, DynBundle::new().add(A(2)))
+        .unwrap();
+    assert!(stack.undo(&mut world).unwrap());
+
+    world.despawn(first);
+    assert_eq!(stack.redo(&mut world), Ok(true));
+    assert_eq!(stack.undo(&mut world), Ok(true));
+    assert_eq!(stack.undo(&mut world), Err(DynBundleError::EntityNotFound));
+    assert_eq!(stack.undo(&mut world), Err(DynBundleError::EntityNotFound));
+    assert!(stack.can_undo());
+    assert!(stack.can_redo());
+
+    assert_eq!(stack.redo(&mut world), Ok(true));
+    assert_eq!(world.get::<A>(second), Some(&A(2)));
+}
+
+#[test]
+fn failed_redo_keeps_the_history() {
+    let mut world = world();
+    let entity = world.spawn(A(1)).id();
+    let mut stack = UndoStack::default();
+    stack
+        .apply(&mut world, entity, DynBundle::new().add(A(2)))
+        .unwrap();
+    assert!(stack.undo(&mut world).unwrap());
+
+    world.despawn(entity);
+    assert_eq!(stack.redo(&mut world), Err(DynBundleError::EntityNotFound));
+    assert!(stack.can_redo());
+    assert!(!stack.can_undo());
+}
+
+#[test]
+fn changes_outside_the_bundle_are_not_undone() {
+    let mut world = world();
+    world.add_observer(|trigger: Trigger<OnAdd, Marker>, mut b: Query<&mut B>| {
+        b.get_mut(trigger.entity()).unwrap().0 = 99;
+    });
+    let entity = world.spawn((A(1), B(1))).id();
+    let mut stack = UndoStack::default();
+
+    stack
+        .apply(&mut world, entity, DynBundle::new().add(A(2)).add(Marker))
+        .unwrap();
+    assert_eq!(world.get::<B>(entity), Some(&B(99)));
+
+    assert!(stack.undo(&mut world).unwrap());
+    assert_eq!(world.get::<A>(entity), Some(&A(1)));
+    assert!(!world.entity(entity).contains::<Marker>());
+    // `B` isn't part of the bundle, so it wasn't captured before applying.
+    assert_eq!(world.get::<B>(entity), Some(&B(99)));
+}